@@ -0,0 +1,209 @@
+use assert_graph_iso::{
+    assert_graph_eq, canonicalize,
+    graph::{Graph, LabelIterator, NodesIterator, PropertyIterator, RelationshipIterator},
+    PreparedGraph,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use gdl::{CypherValue, Graph as GdlGraph};
+
+/// A gdl graph of `node_count` nodes where every node points to `degree`
+/// pseudo-random targets.
+fn graph(node_count: u64, degree: u64) -> GdlGraph {
+    let mut state = 42_u64;
+    let nodes = (0..node_count).map(|node| format!("(n{}:Node {{ id: {} }})", node, node));
+    let edges = (0..node_count)
+        .flat_map(|source| (0..degree).map(move |_| source))
+        .map(|source| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1);
+            format!("(n{})-[:REL]->(n{})", source, (state >> 33) % node_count)
+        })
+        .collect::<Vec<_>>();
+
+    nodes
+        .chain(edges)
+        .collect::<Vec<_>>()
+        .join(", ")
+        .parse()
+        .unwrap()
+}
+
+/// A gdl graph of `node_count` nodes in a cycle where every node and every
+/// relationship has `property_count` properties.
+fn property_graph(node_count: u64, property_count: u64) -> GdlGraph {
+    let properties = |element: u64| {
+        (0..property_count)
+            .map(|key| match key % 3 {
+                0 => format!("p{}: {}", key, element * key),
+                1 => format!("p{}: 'v{}'", key, element + key),
+                _ => format!("p{}: {}.5", key, element % 7),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let nodes = (0..node_count).map(|node| format!("(n{}:Node {{ {} }})", node, properties(node)));
+    let edges = (0..node_count).map(|node| {
+        format!(
+            "(n{})-[:REL {{ {} }}]->(n{})",
+            node,
+            properties(node + 1),
+            (node + 1) % node_count
+        )
+    });
+
+    nodes
+        .chain(edges)
+        .collect::<Vec<_>>()
+        .join(", ")
+        .parse()
+        .unwrap()
+}
+
+/// Delegates to the gdl adapter, but keeps the default `Graph::relationships`,
+/// which looks up the outgoing relationships of every node.
+struct PerNode<'a>(&'a GdlGraph);
+
+impl Graph for PerNode<'_> {
+    type NodeId = str;
+
+    type NodeLabel = str;
+
+    type RelationshipType = str;
+
+    type PropertyKey = str;
+
+    type PropertyValue = CypherValue;
+
+    fn nodes(&self) -> NodesIterator<'_, &Self::NodeId> {
+        Graph::nodes(self.0)
+    }
+
+    fn node_labels(&self, node_id: &Self::NodeId) -> LabelIterator<'_, &Self::NodeLabel> {
+        Graph::node_labels(self.0, node_id)
+    }
+
+    fn node_properties(
+        &self,
+        node_id: &Self::NodeId,
+    ) -> PropertyIterator<'_, &Self::PropertyKey, &Self::PropertyValue> {
+        Graph::node_properties(self.0, node_id)
+    }
+
+    fn outgoing_relationships<'a, 'b: 'a>(
+        &'a self,
+        node_id: &'b Self::NodeId,
+    ) -> RelationshipIterator<
+        'a,
+        &'a Self::NodeId,
+        &'a Self::RelationshipType,
+        &'a Self::PropertyKey,
+        &'a Self::PropertyValue,
+    > {
+        Graph::outgoing_relationships(self.0, node_id)
+    }
+
+    fn incoming_relationships<'a, 'b: 'a>(
+        &'a self,
+        node_id: &'b Self::NodeId,
+    ) -> RelationshipIterator<
+        'a,
+        &'a Self::NodeId,
+        &'a Self::RelationshipType,
+        &'a Self::PropertyKey,
+        &'a Self::PropertyValue,
+    > {
+        Graph::incoming_relationships(self.0, node_id)
+    }
+
+    fn property_value(&self, value: &Self::PropertyValue) -> assert_graph_iso::Value {
+        Graph::property_value(self.0, value)
+    }
+}
+
+fn bench_canonicalize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("canonicalize_gdl");
+    group.sample_size(10);
+
+    for node_count in [500, 1_000] {
+        let graph = graph(node_count, 4);
+
+        group.bench_with_input(
+            BenchmarkId::new("single_pass", node_count),
+            &graph,
+            |b, graph| b.iter(|| canonicalize(graph)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("per_node", node_count),
+            &graph,
+            |b, graph| b.iter(|| canonicalize(&PerNode(graph))),
+        );
+    }
+
+    group.finish();
+}
+
+/// Compares one expected graph against many candidates, canonicalizing the
+/// expected graph for every comparison or only once.
+fn bench_compare_many(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compare_many_gdl");
+    group.sample_size(10);
+
+    let expected = graph(500, 4);
+    for candidate_count in [10, 50] {
+        let candidates = (0..candidate_count)
+            .map(|_| graph(500, 4))
+            .collect::<Vec<_>>();
+
+        group.bench_with_input(
+            BenchmarkId::new("assert_graph_eq", candidate_count),
+            &candidates,
+            |b, candidates| {
+                b.iter(|| {
+                    candidates
+                        .iter()
+                        .for_each(|candidate| assert_graph_eq!(expected, *candidate))
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("prepared", candidate_count),
+            &candidates,
+            |b, candidates| {
+                b.iter(|| {
+                    let expected = PreparedGraph::new(&expected);
+                    candidates
+                        .iter()
+                        .for_each(|candidate| assert!(expected.matches(candidate)))
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn bench_canonicalize_properties(c: &mut Criterion) {
+    let mut group = c.benchmark_group("canonicalize_properties_gdl");
+    group.sample_size(10);
+
+    for property_count in [10, 50] {
+        let graph = property_graph(1_000, property_count);
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(property_count),
+            &graph,
+            |b, graph| b.iter(|| canonicalize(graph)),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_canonicalize,
+    bench_canonicalize_properties,
+    bench_compare_many
+);
+criterion_main!(benches);
@@ -0,0 +1,52 @@
+use assert_graph_iso::{
+    canonicalize,
+    petgraph::{CsrGraph, PetGraph},
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use petgraph::csr::Csr;
+
+/// Edges of a graph where every node points to `degree` pseudo-random targets.
+fn edges(node_count: u32, degree: u32) -> Vec<(u32, u32)> {
+    let mut state = 42_u64;
+    let mut edges = (0..node_count)
+        .flat_map(|source| (0..degree).map(move |_| source))
+        .map(|source| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1);
+            (source, (state >> 33) as u32 % node_count)
+        })
+        .collect::<Vec<_>>();
+    edges.sort_unstable();
+    edges.dedup();
+    edges
+}
+
+fn bench_canonicalize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("canonicalize");
+
+    for node_count in [1_000, 10_000] {
+        let edges = edges(node_count, 4);
+
+        let mut graph = petgraph::Graph::<(), ()>::new();
+        let nodes = (0..node_count)
+            .map(|_| graph.add_node(()))
+            .collect::<Vec<_>>();
+        edges.iter().for_each(|&(source, target)| {
+            graph.add_edge(nodes[source as usize], nodes[target as usize], ());
+        });
+        let csr = Csr::<(), ()>::from_sorted_edges(&edges).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("Graph", node_count), &graph, |b, graph| {
+            b.iter(|| canonicalize(&PetGraph::new(graph)))
+        });
+        group.bench_with_input(BenchmarkId::new("Csr", node_count), &csr, |b, csr| {
+            b.iter(|| canonicalize(&CsrGraph::new(csr)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_canonicalize);
+criterion_main!(benches);
@@ -0,0 +1,83 @@
+//! Compares two graphs given as GDL files.
+//!
+//! Exits with `0` if the graphs are isomorphic and with `1` otherwise, after
+//! printing the diff of their canonical rows. Invalid arguments or files
+//! exit with `2`.
+
+use std::{convert::TryFrom, env, fs, process::ExitCode};
+
+use assert_graph_iso::{equals_with, gdl::GdlGraph, graph_diff_with, CompareOptions};
+
+const USAGE: &str = "usage: assert-graph-iso [--topology-only] [--undirected] \
+                     [--ignore-key <key>]... <left.gdl> <right.gdl>";
+
+struct Args {
+    options: CompareOptions,
+    left: String,
+    right: String,
+}
+
+fn main() -> ExitCode {
+    match run(env::args().skip(1)) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(args: impl Iterator<Item = String>) -> Result<bool, String> {
+    let args = parse_args(args)?;
+    let left = read_graph(&args.left)?;
+    let right = read_graph(&args.right)?;
+
+    if equals_with(&left, &right, &args.options) {
+        println!("graphs are isomorphic");
+        Ok(true)
+    } else {
+        println!("graphs are not isomorphic");
+        print!("{}", graph_diff_with(&left, &right, &args.options));
+        Ok(false)
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut options = CompareOptions::new();
+    let mut paths = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--topology-only" => options.topology_only = true,
+            "--undirected" => options = options.treat_as_undirected(true),
+            "--ignore-key" => {
+                let key = args
+                    .next()
+                    .ok_or_else(|| format!("--ignore-key requires a key\n{}", USAGE))?;
+                options = options.ignore_property_keys(Some(key));
+            }
+            flag if flag.starts_with("--") => {
+                return Err(format!("unknown flag {}\n{}", flag, USAGE))
+            }
+            _ => paths.push(arg),
+        }
+    }
+
+    match <[String; 2]>::try_from(paths) {
+        Ok([left, right]) => Ok(Args {
+            options,
+            left,
+            right,
+        }),
+        Err(_) => Err(USAGE.to_string()),
+    }
+}
+
+fn read_graph(path: &str) -> Result<GdlGraph, String> {
+    let input =
+        fs::read_to_string(path).map_err(|error| format!("cannot read {}: {}", path, error))?;
+    input
+        .parse::<GdlGraph>()
+        .map_err(|error| format!("invalid gdl in {}: {}", path, error))
+}
@@ -0,0 +1,1363 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{
+    cmp::Ordering,
+    fmt::{self, Debug, Display, Write as _},
+    hash::{Hash, Hasher},
+};
+
+use crate::{
+    error::{GraphError, VersionMismatch},
+    timeout::{Deadline, Timeout},
+    CompareOptions, DuplicatePropertyKey, Graph, ParallelEdgeConflict, PropertyOwner,
+    PropertyRenderer, Value,
+};
+
+pub(crate) type Properties = Vec<(String, Value)>;
+
+/// The version of the canonical rendering, see `canonical_form_version`.
+pub(crate) const FORMAT_VERSION: u32 = 1;
+
+/// Why reading a graph failed.
+pub(crate) enum ReadError {
+    // Without `std`, there is no deadline that could time out.
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    Timeout(Timeout),
+    Graph(GraphError),
+}
+
+impl From<Timeout> for ReadError {
+    fn from(timeout: Timeout) -> Self {
+        ReadError::Timeout(timeout)
+    }
+}
+
+impl From<GraphError> for ReadError {
+    fn from(error: GraphError) -> Self {
+        ReadError::Graph(error)
+    }
+}
+
+pub(crate) struct Node<'a, Id: ?Sized> {
+    pub(crate) id: &'a Id,
+    pub(crate) labels: Vec<String>,
+    pub(crate) properties: Properties,
+}
+
+pub(crate) struct Relationship {
+    pub(crate) source: usize,
+    pub(crate) target: usize,
+    pub(crate) rel_type: String,
+    pub(crate) properties: Properties,
+    /// Set for a merged pair of reciprocal relationships, which is rendered
+    /// as `-[...]-` in the rows of both endpoints.
+    pub(crate) undirected: bool,
+    /// The id from `Graph::relationship_id`, only read if
+    /// `CompareOptions::use_relationship_ids` is set.
+    pub(crate) id: Option<String>,
+}
+
+impl Relationship {
+    /// Source, target and whether the relationship is undirected, with the
+    /// endpoints of undirected relationships in ascending order, so both
+    /// orientations of an undirected relationship share a key.
+    pub(crate) fn endpoints(&self) -> (usize, usize, bool) {
+        if self.undirected {
+            (
+                self.source.min(self.target),
+                self.source.max(self.target),
+                true,
+            )
+        } else {
+            (self.source, self.target, false)
+        }
+    }
+
+    /// Renders the type, followed by the id if there is one, as in `[:T #id ]`.
+    pub(crate) fn type_and_id(&self) -> TypeAndId<'_> {
+        TypeAndId(self)
+    }
+}
+
+pub(crate) struct TypeAndId<'r>(&'r Relationship);
+
+impl Display for TypeAndId<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.rel_type)?;
+        match &self.0.id {
+            Some(id) => write!(f, " #{}", id),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A graph read once through the `Graph` trait, with node ids replaced by
+/// positions and all labels, types and properties in owned form.
+pub(crate) struct Collected<'a, Id: ?Sized> {
+    pub(crate) nodes: Vec<Node<'a, Id>>,
+    pub(crate) relationships: Vec<Relationship>,
+    /// Keeps repeated labels of a node when rendering it instead of
+    /// treating its labels as a set.
+    pub(crate) labels_as_multiset: bool,
+}
+
+/// The canonical string representation of a graph.
+///
+/// Two graphs are isomorphic if their canonical forms are equal, so the
+/// form can be computed once and compared against many others, stored or
+/// used as a key in a `HashSet` to deduplicate graphs.
+///
+/// ```
+/// use std::collections::HashSet;
+///
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::canonical_form;
+///
+/// let graphs = ["(a)-->(b)", "(x)<--(y)", "(a)-->(a)"]
+///     .iter()
+///     .map(|gdl| gdl.parse::<GdlGraph>().unwrap())
+///     .collect::<Vec<_>>();
+///
+/// let distinct = graphs.iter().map(canonical_form).collect::<HashSet<_>>();
+/// assert_eq!(distinct.len(), 2);
+/// ```
+///
+/// The rendering of the form is versioned, see `canonical_form_version`.
+/// Comparing or ordering forms of different versions panics, as their
+/// bodies cannot be compared meaningfully; use `try_eq` to handle forms that
+/// might stem from another release.
+///
+/// With the `serde` feature, the form is serialized with its version and
+/// body, e.g., to store expected forms in fixtures or snapshots.
+#[derive(Clone, Debug, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CanonicalForm {
+    version: u32,
+    body: String,
+}
+
+impl CanonicalForm {
+    /// Creates a form from a stored version and body, e.g., from a golden
+    /// file written by an earlier release.
+    pub fn new(version: u32, body: impl Into<String>) -> Self {
+        Self {
+            version,
+            body: body.into(),
+        }
+    }
+
+    /// Creates a form of the current version from a body, e.g., from a
+    /// golden file written with `to_string` by this release.
+    pub fn from_raw(body: impl Into<String>) -> Self {
+        Self::new(FORMAT_VERSION, body)
+    }
+
+    /// The version of the rendering this form was created with.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.body
+    }
+
+    /// Like `==`, but fails instead of panicking if the forms were rendered
+    /// with different versions.
+    pub fn try_eq(&self, other: &Self) -> Result<bool, VersionMismatch> {
+        self.check_version(other)?;
+        Ok(self.body == other.body)
+    }
+
+    fn check_version(&self, other: &Self) -> Result<(), VersionMismatch> {
+        if self.version == other.version {
+            Ok(())
+        } else {
+            Err(VersionMismatch {
+                left: self.version,
+                right: other.version,
+            })
+        }
+    }
+
+    fn expect_same_version(&self, other: &Self) {
+        if let Err(error) = self.check_version(other) {
+            panic!("cannot compare canonical forms: {}", error);
+        }
+    }
+
+    /// Hashes the form with 64-bit FNV-1a, which is the same on every
+    /// platform and in every release, see `canonical_hash`. Only the body is
+    /// hashed, so the hash changes with the rendering, too.
+    pub fn stable_hash(&self) -> u64 {
+        let mut hash = StableHasher::new();
+        hash.write(self.body.as_bytes());
+        hash.finish()
+    }
+}
+
+impl PartialEq for CanonicalForm {
+    fn eq(&self, other: &Self) -> bool {
+        self.expect_same_version(other);
+        self.body == other.body
+    }
+}
+
+impl Hash for CanonicalForm {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.version.hash(state);
+        self.body.hash(state);
+    }
+}
+
+impl PartialOrd for CanonicalForm {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CanonicalForm {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.expect_same_version(other);
+        self.body.cmp(&other.body)
+    }
+}
+
+/// 64-bit FNV-1a, used instead of `DefaultHasher` whose output may change
+/// between Rust releases.
+struct StableHasher(u64);
+
+impl StableHasher {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        bytes.iter().for_each(|&byte| {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        });
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Maps node ids to their position in `Collected::nodes`.
+#[cfg(feature = "std")]
+type NodeIndex<'a, Id> = std::collections::HashMap<&'a Id, usize>;
+
+/// Maps node ids to their position in `Collected::nodes`. Without `std`,
+/// there is no hash map, so ids are grouped by their `StableHasher` hash.
+#[cfg(not(feature = "std"))]
+struct NodeIndex<'a, Id: ?Sized>(BTreeMap<u64, Vec<(&'a Id, usize)>>);
+
+#[cfg(not(feature = "std"))]
+impl<'a, Id: Hash + Eq + ?Sized> NodeIndex<'a, Id> {
+    fn new() -> Self {
+        Self(BTreeMap::new())
+    }
+
+    fn hash(id: &Id) -> u64 {
+        let mut hasher = StableHasher::new();
+        id.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn get(&self, id: &Id) -> Option<&usize> {
+        self.0
+            .get(&Self::hash(id))?
+            .iter()
+            .find(|(candidate, _)| *candidate == id)
+            .map(|(_, position)| position)
+    }
+
+    fn contains_key(&self, id: &Id) -> bool {
+        self.get(id).is_some()
+    }
+
+    fn insert(&mut self, id: &'a Id, position: usize) {
+        self.0
+            .entry(Self::hash(id))
+            .or_default()
+            .push((id, position));
+    }
+}
+
+impl Display for CanonicalForm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.body)
+    }
+}
+
+impl From<CanonicalForm> for String {
+    fn from(form: CanonicalForm) -> Self {
+        form.body
+    }
+}
+
+pub(crate) fn canonical_form<G: Graph>(graph: &G, options: &CompareOptions) -> CanonicalForm {
+    CanonicalForm::new(FORMAT_VERSION, canonicalize(graph, options))
+}
+
+pub(crate) fn canonicalize<G: Graph>(graph: &G, options: &CompareOptions) -> String {
+    render_ordered(&collect(graph, options), options)
+}
+
+pub(crate) fn canonical_rows<G: Graph>(graph: &G, options: &CompareOptions) -> Vec<String> {
+    collect(graph, options).render_rows()
+}
+
+/// Returns the parts of the canonical rows, sorted like the rows.
+#[cfg(feature = "json")]
+pub(crate) fn canonical_adjacencies<G: Graph>(
+    graph: &G,
+    options: &CompareOptions,
+) -> Vec<(String, Vec<String>, Vec<String>)> {
+    let collected = collect(graph, options);
+    let canonical_nodes = collected.canonical_nodes();
+    let mut adjacencies = collected
+        .adjacencies_with(&canonical_nodes)
+        .into_iter()
+        .map(|(node, out, incoming)| (node.clone(), out, incoming))
+        .collect::<Vec<_>>();
+    adjacencies.sort();
+    adjacencies
+}
+
+/// Hashes the canonical form row by row, without joining the rows.
+pub(crate) fn canonical_hash<G: Graph>(graph: &G, options: &CompareOptions) -> u64 {
+    let mut hash = StableHasher::new();
+    collect(graph, options)
+        .render_rows()
+        .iter()
+        .enumerate()
+        .for_each(|(position, row)| {
+            if position > 0 {
+                hash.write(b"\n");
+            }
+            hash.write(row.as_bytes());
+        });
+    hash.finish()
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn canonicalize_within<G: Graph>(
+    graph: &G,
+    options: &CompareOptions,
+    deadline: &Deadline,
+) -> Result<String, Timeout> {
+    match collect_within(graph, options, Some(deadline)) {
+        Ok(collected) => collected.render_within(Some(deadline)),
+        Err(ReadError::Timeout(timeout)) => Err(timeout),
+        Err(ReadError::Graph(error)) => panic!("{}", error),
+    }
+}
+
+pub(crate) fn try_canonicalize<G: Graph>(
+    graph: &G,
+    options: &CompareOptions,
+) -> Result<String, GraphError> {
+    match collect_within(graph, options, None) {
+        Ok(collected) => Ok(render_ordered(&collected, options)),
+        Err(ReadError::Timeout(_)) => unreachable!("reading without a deadline cannot time out"),
+        Err(ReadError::Graph(error)) => Err(error),
+    }
+}
+
+/// Like `Collected::render`, but orders the rows of the nodes by the
+/// `node_order_key` of the options, if there is one.
+fn render_ordered<Id: Debug + ?Sized>(
+    collected: &Collected<'_, Id>,
+    options: &CompareOptions,
+) -> String {
+    let key = match &options.node_order_key {
+        Some(key) => key,
+        None => return collected.render(),
+    };
+    let mut rows = collected
+        .rows()
+        .into_iter()
+        .zip(&collected.nodes)
+        .map(|(row, node)| ((key.0)(&format!("{:?}", node.id), &row), row))
+        .collect::<Vec<_>>();
+    rows.sort_by(|(left_key, left_row), (right_key, right_row)| {
+        left_key
+            .canonical_cmp(right_key)
+            .then_with(|| left_row.cmp(right_row))
+    });
+
+    let mut matrix = collected.render_rows();
+    matrix.splice(..rows.len(), rows.into_iter().map(|(_, row)| row));
+    matrix.join("\n")
+}
+
+/// Reads the graph and applies all options that rewrite nodes or relationships.
+pub(crate) fn collect<'a, G: Graph>(
+    graph: &'a G,
+    options: &CompareOptions,
+) -> Collected<'a, G::NodeId> {
+    match collect_within(graph, options, None) {
+        Ok(collected) => collected,
+        Err(ReadError::Timeout(_)) => unreachable!("reading without a deadline cannot time out"),
+        Err(ReadError::Graph(error)) => panic!("{}", error),
+    }
+}
+
+fn collect_within<'a, G: Graph>(
+    graph: &'a G,
+    options: &CompareOptions,
+    deadline: Option<&Deadline>,
+) -> Result<Collected<'a, G::NodeId>, ReadError> {
+    let mut collected = Collected::read(graph, deadline)?;
+
+    if options.use_relationship_ids {
+        collected.read_relationship_ids(graph);
+    }
+
+    if let Some(key) = &options.relationship_type_property {
+        collected.relationship_types_from_property(key);
+    }
+
+    if !options.labels_as_properties.is_empty() {
+        collected.labels_as_properties(&options.labels_as_properties);
+    }
+
+    if !options.properties_as_labels.is_empty() {
+        collected.properties_as_labels(&options.properties_as_labels);
+    }
+
+    if let Some((key, threshold)) = &options.min_edge_weight {
+        collected.relationships.retain(|rel| {
+            rel.properties
+                .iter()
+                .filter(|(candidate, _)| candidate == key)
+                .filter_map(|(_, value)| value.as_number())
+                .all(|weight| weight >= *threshold)
+        });
+    }
+
+    if !options.ignored_relationship_types.is_empty() {
+        collected
+            .relationships
+            .retain(|rel| !options.ignored_relationship_types.contains(&rel.rel_type));
+    }
+
+    if !options.ignored_property_keys.is_empty() {
+        collected.retain_properties(|key, _| !options.ignored_property_keys.contains(key));
+    }
+
+    if !options.ignored_relationship_property_keys.is_empty() {
+        collected.relationships.iter_mut().for_each(|rel| {
+            rel.properties
+                .retain(|(key, _)| !options.ignored_relationship_property_keys.contains(key))
+        });
+    }
+
+    if options.ignore_relationship_properties {
+        collected
+            .relationships
+            .iter_mut()
+            .for_each(|rel| rel.properties.clear());
+    }
+
+    if options.null_as_absent {
+        collected.retain_properties(|_, value| *value != Value::Null);
+    }
+
+    if let Some(transform) = &options.property_transform {
+        collected.map_properties(|key, value| {
+            if let Some(keys) = &transform.keys {
+                *key = keys(key);
+            }
+            if let Some(values) = &transform.values {
+                *value = values(core::mem::replace(value, Value::Null));
+            }
+        });
+    }
+
+    if options.coerce_numeric_strings {
+        collected.map_properties(|_, value| value.coerce_numeric_string());
+    }
+
+    if options.normalize_numbers {
+        collected.map_properties(|_, value| value.normalize_number());
+    }
+
+    if options.sort_lists {
+        collected.map_properties(|_, value| value.sort_lists());
+    }
+
+    if let Some(policy) = options.on_duplicate_property_key {
+        collected.resolve_duplicate_keys(policy);
+    }
+
+    if let Some(epsilon) = options.node_float_epsilon {
+        collected.map_node_values(|value| value.round_to(epsilon));
+    }
+
+    if let Some(epsilon) = options.float_epsilon {
+        collected.map_properties(|_, value| value.round_to(epsilon));
+    }
+
+    if options.merge_parallel_edge_properties {
+        collected.merge_parallel_relationships(options.parallel_edge_conflict);
+    }
+
+    if let Some(key) = &options.sum_parallel_edge_weights {
+        collected.sum_parallel_weights(key);
+    }
+
+    if options.merge_reciprocal_edges {
+        collected.merge_reciprocal_relationships();
+    }
+
+    if options.treat_as_undirected {
+        collected
+            .relationships
+            .iter_mut()
+            .for_each(|rel| rel.undirected = true);
+    }
+
+    if !options.symmetric_rel_types.is_empty() {
+        collected
+            .relationships
+            .iter_mut()
+            .filter(|rel| options.symmetric_rel_types.contains(&rel.rel_type))
+            .for_each(|rel| rel.undirected = true);
+    }
+
+    collected.labels_as_multiset = options.labels_as_multiset;
+
+    if options.case_insensitive_labels {
+        collected
+            .nodes
+            .iter_mut()
+            .flat_map(|node| node.labels.iter_mut())
+            .for_each(|label| *label = label.to_lowercase());
+    }
+
+    if options.case_insensitive_rel_types {
+        collected
+            .relationships
+            .iter_mut()
+            .for_each(|rel| rel.rel_type = rel.rel_type.to_lowercase());
+    }
+
+    if options.topology_only {
+        collected.nodes.iter_mut().for_each(|node| {
+            node.labels.clear();
+            node.properties.clear();
+        });
+        collected
+            .relationships
+            .iter_mut()
+            .for_each(|rel| rel.properties.clear());
+    }
+
+    if let Some(precision) = options.float_precision {
+        collected.map_properties(|_, value| value.format_float(precision));
+    }
+
+    if let Some(renderer) = &options.property_renderer {
+        collected.render_properties(renderer.as_ref());
+    }
+
+    if options.dedup_parallel_edges {
+        collected.dedup_parallel_relationships();
+    }
+
+    Ok(collected)
+}
+
+impl<'a, Id: Debug + Hash + Eq + ?Sized> Collected<'a, Id> {
+    pub(crate) fn new<G: Graph<NodeId = Id>>(graph: &'a G) -> Self {
+        match Self::read(graph, None) {
+            Ok(collected) => collected,
+            Err(ReadError::Timeout(_)) => {
+                unreachable!("reading without a deadline cannot time out")
+            }
+            Err(ReadError::Graph(error)) => panic!("{}", error),
+        }
+    }
+
+    /// Reads the graph, checking the deadline for every node and relationship.
+    ///
+    /// Fails if a node is yielded more than once or if a relationship points
+    /// at a node that is not part of the graph.
+    pub(crate) fn read<G: Graph<NodeId = Id>>(
+        graph: &'a G,
+        deadline: Option<&Deadline>,
+    ) -> Result<Self, ReadError> {
+        let mut nodes = Vec::new();
+        let mut index = NodeIndex::new();
+
+        for node in graph.nodes() {
+            if index.contains_key(node) {
+                return Err(GraphError::DuplicateNode {
+                    id: format!("{:?}", node),
+                }
+                .into());
+            }
+            if let Some(deadline) = deadline {
+                deadline.check(nodes.len(), 0)?;
+            }
+            index.insert(node, nodes.len());
+            nodes.push(Node {
+                id: node,
+                labels: graph
+                    .node_labels(node)
+                    .map(|label| label.to_string())
+                    .collect(),
+                properties: graph
+                    .node_properties(node)
+                    .map(|(key, value)| (key.to_string(), graph.property_value(value)))
+                    .collect(),
+            });
+        }
+
+        let mut relationships = Vec::new();
+        let undirected = !graph.is_directed();
+        let mut sources_seen = vec![false; nodes.len()];
+        let mut sources_read = 0;
+        let position = |id: &Id| {
+            index
+                .get(id)
+                .copied()
+                .ok_or_else(|| GraphError::MissingNode {
+                    id: format!("{:?}", id),
+                })
+        };
+
+        for (source, (target, rel_type), rel_properties) in graph.relationships() {
+            let source = position(source)?;
+            let target = position(target)?;
+
+            if !sources_seen[source] {
+                sources_seen[source] = true;
+                sources_read += 1;
+            }
+            if let Some(deadline) = deadline {
+                deadline.check(nodes.len(), sources_read)?;
+            }
+
+            relationships.push(Relationship {
+                source,
+                target,
+                rel_type: rel_type.to_string(),
+                properties: rel_properties
+                    .map(|(key, value)| (key.to_string(), graph.property_value(value)))
+                    .collect(),
+                undirected,
+                id: None,
+            });
+        }
+
+        Ok(Self {
+            nodes,
+            relationships,
+            labels_as_multiset: false,
+        })
+    }
+
+    /// Sets the id of every relationship from `Graph::relationship_id`.
+    ///
+    /// Relationships are kept in the order of `Graph::relationships`, so the
+    /// position of a relationship among those of its source is its position
+    /// among the relationships with the same source read before it.
+    pub(crate) fn read_relationship_ids<G: Graph<NodeId = Id>>(&mut self, graph: &G) {
+        let mut positions = vec![0; self.nodes.len()];
+        let nodes = &self.nodes;
+        self.relationships.iter_mut().for_each(|rel| {
+            rel.id = graph.relationship_id(nodes[rel.source].id, positions[rel.source]);
+            positions[rel.source] += 1;
+        });
+    }
+
+    /// Replaces every pair of relationships `(a)-[:T]->(b)` and `(b)-[:T]->(a)`
+    /// with equal properties by a single undirected relationship.
+    ///
+    /// Relationships without a reciprocal stay directed. Panics if reciprocal
+    /// relationships exist but none of them agree on their properties.
+    pub(crate) fn merge_reciprocal_relationships(&mut self) {
+        let mut groups =
+            BTreeMap::<(usize, usize, String, Option<String>), (Vec<usize>, Vec<usize>)>::new();
+
+        self.relationships
+            .iter()
+            .enumerate()
+            .filter(|(_, rel)| rel.source != rel.target && !rel.undirected)
+            .for_each(|(position, rel)| {
+                let (low, high) = (rel.source.min(rel.target), rel.source.max(rel.target));
+                let group = groups
+                    .entry((low, high, rel.rel_type.clone(), rel.id.clone()))
+                    .or_default();
+                if rel.source == low {
+                    group.0.push(position);
+                } else {
+                    group.1.push(position);
+                }
+            });
+
+        let mut merged = vec![false; self.relationships.len()];
+        let mut removed = vec![false; self.relationships.len()];
+
+        groups.into_values().for_each(|(forward, backward)| {
+            let tokens = |position: &usize| property_tokens(&self.relationships[*position].properties);
+            let mut backward = backward
+                .into_iter()
+                .map(|position| (tokens(&position), position))
+                .collect::<Vec<_>>();
+            let mut unmatched = Vec::new();
+
+            forward.into_iter().for_each(|position| {
+                let properties = tokens(&position);
+                match backward.iter().position(|(candidate, _)| *candidate == properties) {
+                    Some(reciprocal) => {
+                        merged[position] = true;
+                        removed[backward.swap_remove(reciprocal).1] = true;
+                    }
+                    None => unmatched.push(position),
+                }
+            });
+
+            if let (Some(forward), Some((_, backward))) = (unmatched.first(), backward.first()) {
+                let (forward, backward) = (&self.relationships[*forward], &self.relationships[*backward]);
+                panic!(
+                    "Conflicting properties on reciprocal relationships {:?}-[:{} {}]->{:?} and {:?}-[:{} {}]->{:?}",
+                    self.nodes[forward.source].id,
+                    forward.rel_type,
+                    canonical_properties(&forward.properties),
+                    self.nodes[forward.target].id,
+                    self.nodes[backward.source].id,
+                    backward.rel_type,
+                    canonical_properties(&backward.properties),
+                    self.nodes[backward.target].id,
+                );
+            }
+        });
+
+        let mut position = 0;
+        self.relationships.retain_mut(|rel| {
+            rel.undirected |= merged[position];
+            position += 1;
+            !removed[position - 1]
+        });
+    }
+}
+
+impl<'a, Id: ?Sized> Collected<'a, Id> {
+    /// Keeps a single value for every property key of a node or relationship.
+    pub(crate) fn resolve_duplicate_keys(&mut self, policy: DuplicatePropertyKey) {
+        self.nodes
+            .iter_mut()
+            .for_each(|node| resolve_duplicate_keys(&mut node.properties, policy));
+        self.relationships
+            .iter_mut()
+            .for_each(|rel| resolve_duplicate_keys(&mut rel.properties, policy));
+    }
+
+    /// Replaces the type of every relationship carrying the property `key`
+    /// with the value of that property and removes the property.
+    pub(crate) fn relationship_types_from_property(&mut self, key: &str) {
+        self.relationships.iter_mut().for_each(|rel| {
+            if let Some(position) = rel.properties.iter().position(|(k, _)| k == key) {
+                rel.rel_type = match rel.properties.remove(position).1 {
+                    Value::String(rel_type) => rel_type,
+                    value => value.to_string(),
+                };
+                rel.properties.retain(|(k, _)| k != key);
+            }
+        });
+    }
+
+    pub(crate) fn labels_as_properties(&mut self, mapping: &BTreeMap<String, (String, Value)>) {
+        self.nodes.iter_mut().for_each(|node| {
+            let properties = &mut node.properties;
+            node.labels.retain(|label| match mapping.get(label) {
+                Some(property) => {
+                    properties.push(property.clone());
+                    false
+                }
+                None => true,
+            });
+        });
+    }
+
+    pub(crate) fn properties_as_labels(&mut self, mapping: &[((String, Value), String)]) {
+        self.nodes.iter_mut().for_each(|node| {
+            let labels = &mut node.labels;
+            node.properties.retain(|property| {
+                match mapping.iter().find(|(candidate, _)| candidate == property) {
+                    Some((_, label)) => {
+                        labels.push(label.clone());
+                        false
+                    }
+                    None => true,
+                }
+            });
+        });
+    }
+
+    /// Keeps the induced subgraph of all nodes matching `f`, i.e., only
+    /// relationships between two retained nodes are kept.
+    pub(crate) fn retain_nodes(&mut self, f: impl Fn(&Node<'a, Id>) -> bool) {
+        let mut positions = Vec::with_capacity(self.nodes.len());
+        let mut retained = 0;
+        self.nodes.iter().for_each(|node| {
+            if f(node) {
+                positions.push(Some(retained));
+                retained += 1;
+            } else {
+                positions.push(None);
+            }
+        });
+
+        let mut position = 0;
+        self.nodes.retain(|_| {
+            position += 1;
+            positions[position - 1].is_some()
+        });
+
+        self.relationships
+            .retain_mut(|rel| match (positions[rel.source], positions[rel.target]) {
+                (Some(source), Some(target)) => {
+                    rel.source = source;
+                    rel.target = target;
+                    true
+                }
+                _ => false,
+            });
+    }
+
+    pub(crate) fn retain_properties(&mut self, f: impl Fn(&str, &Value) -> bool) {
+        self.nodes
+            .iter_mut()
+            .for_each(|node| node.properties.retain(|(key, value)| f(key, value)));
+        self.relationships
+            .iter_mut()
+            .for_each(|rel| rel.properties.retain(|(key, value)| f(key, value)));
+    }
+
+    pub(crate) fn map_properties(&mut self, f: impl Fn(&mut String, &mut Value)) {
+        self.nodes
+            .iter_mut()
+            .flat_map(|node| node.properties.iter_mut())
+            .chain(
+                self.relationships
+                    .iter_mut()
+                    .flat_map(|rel| rel.properties.iter_mut()),
+            )
+            .for_each(|(key, value)| f(key, value));
+    }
+
+    /// Replaces every property value by its rendering as a string value,
+    /// which `Display`s as the rendering itself.
+    pub(crate) fn render_properties(&mut self, renderer: &dyn PropertyRenderer) {
+        let render = |owner, properties: &mut Properties| {
+            properties.iter_mut().for_each(|(key, value)| {
+                *value = Value::String(renderer.render(owner, key, value));
+            })
+        };
+        self.nodes
+            .iter_mut()
+            .for_each(|node| render(PropertyOwner::Node, &mut node.properties));
+        self.relationships
+            .iter_mut()
+            .for_each(|rel| render(PropertyOwner::Relationship, &mut rel.properties));
+    }
+
+    pub(crate) fn map_node_values(&mut self, f: impl Fn(&mut Value)) {
+        self.nodes
+            .iter_mut()
+            .flat_map(|node| node.properties.iter_mut())
+            .for_each(|(_, value)| f(value));
+    }
+
+    /// Replaces every group of relationships sharing source, target, type and id
+    /// by a single relationship holding the union of their properties. Both
+    /// orientations of undirected relationships belong to the same group.
+    pub(crate) fn merge_parallel_relationships(&mut self, conflict: ParallelEdgeConflict) {
+        let mut groups = Vec::<(Relationship, Vec<Properties>)>::new();
+        let mut group_index =
+            BTreeMap::<((usize, usize, bool), String, Option<String>), usize>::new();
+
+        self.relationships.drain(..).for_each(|mut rel| {
+            let key = (rel.endpoints(), rel.rel_type.clone(), rel.id.clone());
+            let properties = core::mem::take(&mut rel.properties);
+            match group_index.get(&key) {
+                Some(&group) => groups[group].1.push(properties),
+                None => {
+                    group_index.insert(key, groups.len());
+                    groups.push((rel, vec![properties]));
+                }
+            }
+        });
+
+        self.relationships = groups
+            .into_iter()
+            .map(|(mut rel, properties)| {
+                rel.properties = merge_properties(properties, conflict);
+                rel
+            })
+            .collect();
+    }
+
+    /// Replaces every group of relationships that share source, target,
+    /// type, id and all properties but the numeric weight `key` by a single
+    /// relationship carrying the sum of their weights. The sum is an integer
+    /// if all weights are integers. Relationships without a single numeric
+    /// weight are left untouched.
+    pub(crate) fn sum_parallel_weights(&mut self, key: &str) {
+        let mut groups = Vec::<Relationship>::new();
+        let mut group_index =
+            BTreeMap::<((usize, usize, bool), String, Option<String>, Vec<String>), usize>::new();
+
+        self.relationships.drain(..).for_each(|mut rel| {
+            let mut weights = rel
+                .properties
+                .iter()
+                .filter(|(candidate, _)| candidate == key);
+            let weight = match (weights.next(), weights.next()) {
+                (Some((_, weight)), None) if weight.as_number().is_some() => weight.clone(),
+                _ => return groups.push(rel),
+            };
+            rel.properties.retain(|(candidate, _)| candidate != key);
+            let group_key = (
+                rel.endpoints(),
+                rel.rel_type.clone(),
+                rel.id.clone(),
+                property_tokens(&rel.properties),
+            );
+            match group_index.get(&group_key) {
+                Some(&group) => {
+                    let sum = &mut groups[group].properties.last_mut().expect("weight").1;
+                    *sum = match (&*sum, weight) {
+                        (Value::Integer(left), Value::Integer(right)) => {
+                            match left.checked_add(right) {
+                                Some(total) => Value::Integer(total),
+                                None => Value::Float(*left as f64 + right as f64),
+                            }
+                        }
+                        (left, right) => Value::Float(
+                            left.as_number().expect("weight") + right.as_number().expect("weight"),
+                        ),
+                    };
+                }
+                None => {
+                    group_index.insert(group_key, groups.len());
+                    rel.properties.push((key.to_string(), weight));
+                    groups.push(rel);
+                }
+            }
+        });
+
+        self.relationships = groups;
+    }
+
+    /// Keeps only the first of every group of relationships that share
+    /// source, target, type, id and properties.
+    pub(crate) fn dedup_parallel_relationships(&mut self) {
+        let mut seen = BTreeSet::new();
+        self.relationships.retain(|rel| {
+            seen.insert((
+                rel.endpoints(),
+                rel.rel_type.clone(),
+                rel.id.clone(),
+                property_tokens(&rel.properties),
+            ))
+        });
+    }
+
+    pub(crate) fn canonical_nodes(&self) -> Vec<String> {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| (&node.labels, &node.properties))
+            .collect();
+        let labels_as_multiset = self.labels_as_multiset;
+        map_all(nodes, |(labels, properties)| {
+            canonical_node(labels, properties, labels_as_multiset)
+        })
+    }
+
+    /// Renders every relationship together with its source and target node.
+    #[cfg(feature = "std")]
+    pub(crate) fn canonical_relationships(&self, canonical_nodes: &[String]) -> Vec<String> {
+        self.relationships
+            .iter()
+            .map(|rel| {
+                let (source, target) = (&canonical_nodes[rel.source], &canonical_nodes[rel.target]);
+                let properties = canonical_properties(&rel.properties);
+                if rel.undirected {
+                    let (source, target) = (source.min(target), source.max(target));
+                    format!(
+                        "{}-[:{} {}]-{}",
+                        source,
+                        rel.type_and_id(),
+                        properties,
+                        target
+                    )
+                } else {
+                    format!(
+                        "{}-[:{} {}]->{}",
+                        source,
+                        rel.type_and_id(),
+                        properties,
+                        target
+                    )
+                }
+            })
+            .collect()
+    }
+
+    /// Renders the sorted canonical rows, followed by the refinement rounds
+    /// if the rows do not determine the graph up to color refinement.
+    pub(crate) fn render(&self) -> String {
+        self.render_rows().join("\n")
+    }
+
+    /// Like `render`, but fails once the deadline passes between two
+    /// refinement rounds.
+    #[cfg(feature = "std")]
+    pub(crate) fn render_within(&self, deadline: Option<&Deadline>) -> Result<String, Timeout> {
+        Ok(self.render_rows_within(deadline)?.join("\n"))
+    }
+
+    /// Renders the lines of `render`.
+    fn render_rows(&self) -> Vec<String> {
+        match self.render_rows_within(None) {
+            Ok(rows) => rows,
+            Err(_) => unreachable!("rendering without a deadline cannot time out"),
+        }
+    }
+
+    fn render_rows_within(&self, deadline: Option<&Deadline>) -> Result<Vec<String>, Timeout> {
+        let canonical_nodes = self.canonical_nodes();
+        let rows = self.rows_with(&canonical_nodes);
+
+        let mut matrix = rows.clone();
+        matrix.sort();
+
+        refinement_rounds(self, &canonical_nodes, rows, deadline)?
+            .into_iter()
+            .for_each(|(round, mut rows)| {
+                rows.sort();
+                matrix.push(format!("refinement {}:", round));
+                matrix.extend(rows);
+            });
+
+        Ok(matrix)
+    }
+
+    /// Renders the canonical row of every node, in node order.
+    pub(crate) fn rows(&self) -> Vec<String> {
+        self.rows_with(&self.canonical_nodes())
+    }
+
+    /// Renders the row of every node, in node order, using `canonical_nodes`
+    /// for the node itself and for its neighbors.
+    pub(crate) fn rows_with(&self, canonical_nodes: &[String]) -> Vec<String> {
+        map_all(
+            self.adjacencies_with(canonical_nodes),
+            |(canonical_node, out_relationships, in_relationships)| {
+                format!(
+                    "{} => out: {} in: {}",
+                    canonical_node,
+                    out_relationships.join(", "),
+                    in_relationships.join(", ")
+                )
+            },
+        )
+    }
+
+    /// Renders every node together with its sorted outgoing and incoming
+    /// relationships, the parts of a row of `rows_with`.
+    pub(crate) fn adjacencies_with<'n>(
+        &self,
+        canonical_nodes: &'n [String],
+    ) -> Vec<(&'n String, Vec<String>, Vec<String>)> {
+        let mut out_adjacencies = vec![Vec::new(); self.nodes.len()];
+        let mut in_adjacencies = vec![Vec::new(); self.nodes.len()];
+        let mut writer = PropertyWriter::default();
+        let mut sorted_properties = String::new();
+
+        self.relationships.iter().for_each(|rel| {
+            sorted_properties.clear();
+            writer.write(&mut sorted_properties, &rel.properties);
+
+            if rel.undirected {
+                out_adjacencies[rel.source].push(format!(
+                    "()-[:{} {}]-{}",
+                    rel.type_and_id(),
+                    sorted_properties,
+                    canonical_nodes[rel.target]
+                ));
+                out_adjacencies[rel.target].push(format!(
+                    "()-[:{} {}]-{}",
+                    rel.type_and_id(),
+                    sorted_properties,
+                    canonical_nodes[rel.source]
+                ));
+                return;
+            }
+
+            out_adjacencies[rel.source].push(format!(
+                "()-[:{} {}]->{}",
+                rel.type_and_id(),
+                sorted_properties,
+                canonical_nodes[rel.target]
+            ));
+
+            in_adjacencies[rel.target].push(format!(
+                "()<-[:{} {}]-{}",
+                rel.type_and_id(),
+                sorted_properties,
+                canonical_nodes[rel.source]
+            ));
+        });
+
+        let adjacencies = canonical_nodes
+            .iter()
+            .zip(out_adjacencies.into_iter().zip(in_adjacencies))
+            .collect();
+        map_all(
+            adjacencies,
+            |(canonical_node, (mut out_relationships, mut in_relationships))| {
+                out_relationships.sort();
+                in_relationships.sort();
+                (canonical_node, out_relationships, in_relationships)
+            },
+        )
+    }
+}
+
+/// Refines the node colors given by the canonical rows, as in the
+/// Weisfeiler-Leman algorithm, until the number of colors stays the same.
+///
+/// A color is the position of a row among the sorted distinct rows of a
+/// round, so it is the same for isomorphic graphs. Every round renders the
+/// rows with colors instead of canonical nodes, including the final round
+/// which does not split any colors anymore. No rounds are returned if the
+/// rows color the nodes exactly like the canonical nodes, in which case the
+/// rows already determine all later rounds.
+fn refinement_rounds<Id: ?Sized>(
+    graph: &Collected<'_, Id>,
+    canonical_nodes: &[String],
+    rows: Vec<String>,
+    deadline: Option<&Deadline>,
+) -> Result<Vec<(usize, Vec<String>)>, Timeout> {
+    let mut colors = colors(&rows);
+    if color_count(&colors) == color_count(&self::colors(canonical_nodes)) {
+        return Ok(Vec::new());
+    }
+
+    let mut rounds = Vec::new();
+    loop {
+        // Every round renders all rows again, so the clock is read before
+        // each one. The whole graph has been read at this point.
+        if let Some(deadline) = deadline {
+            deadline.check_now(graph.nodes.len(), graph.nodes.len())?;
+        }
+        let names = colors
+            .iter()
+            .map(|color| format!("#{}", color))
+            .collect::<Vec<_>>();
+        let rows = graph.rows_with(&names);
+        let refined = self::colors(&rows);
+        let stable = color_count(&refined) == color_count(&colors);
+
+        rounds.push((rounds.len() + 2, rows));
+        colors = refined;
+
+        if stable {
+            return Ok(rounds);
+        }
+    }
+}
+
+/// Colors every row by its position among the sorted distinct rows.
+fn colors(rows: &[String]) -> Vec<usize> {
+    let mut distinct = rows.iter().collect::<Vec<_>>();
+    distinct.sort();
+    distinct.dedup();
+    rows.iter()
+        .map(|row| distinct.binary_search(&row).unwrap())
+        .collect()
+}
+
+fn color_count(colors: &[usize]) -> usize {
+    colors.iter().max().map_or(0, |max| max + 1)
+}
+
+/// Maps every element in order, on all threads of the rayon pool with the
+/// `rayon` feature.
+#[cfg(feature = "rayon")]
+fn map_all<T: Send, R: Send>(items: Vec<T>, f: impl Fn(T) -> R + Send + Sync) -> Vec<R> {
+    use rayon::prelude::*;
+
+    items.into_par_iter().map(f).collect()
+}
+
+#[cfg(not(feature = "rayon"))]
+fn map_all<T, R>(items: Vec<T>, f: impl Fn(T) -> R) -> Vec<R> {
+    items.into_iter().map(f).collect()
+}
+
+fn canonical_node(
+    labels: &[String],
+    properties: &[(String, Value)],
+    labels_as_multiset: bool,
+) -> String {
+    let mut node_labels = labels.iter().collect::<Vec<_>>();
+
+    node_labels.sort();
+    if !labels_as_multiset {
+        node_labels.dedup();
+    }
+
+    let mut node = String::from("(");
+    node_labels.into_iter().for_each(|label| {
+        node.push(':');
+        node.push_str(label);
+    });
+    node.push(' ');
+    PropertyWriter::default().write(&mut node, properties);
+    node.push(')');
+    node
+}
+
+fn canonical_properties(properties: &[(String, Value)]) -> String {
+    let mut sorted_properties = String::new();
+    PropertyWriter::default().write(&mut sorted_properties, properties);
+    sorted_properties
+}
+
+/// Renders properties as `{ key: value, ... }`, sorted and without
+/// duplicates, like `property_tokens`.
+///
+/// Every property is written into one scratch buffer and sorted as a slice
+/// of it, so no string is allocated per property. The buffers are reused
+/// when rendering the properties of many elements with the same writer.
+#[derive(Default)]
+struct PropertyWriter {
+    scratch: String,
+    tokens: Vec<(usize, usize)>,
+}
+
+impl PropertyWriter {
+    /// Appends the properties to `out`, or nothing if there are none.
+    fn write(&mut self, out: &mut String, properties: &[(String, Value)]) {
+        if properties.is_empty() {
+            return;
+        }
+
+        self.scratch.clear();
+        self.tokens.clear();
+        properties.iter().for_each(|(key, value)| {
+            let start = self.scratch.len();
+            // Writing into a `String` cannot fail.
+            let _ = write!(self.scratch, "{}: {}", key, value);
+            self.tokens.push((start, self.scratch.len()));
+        });
+
+        let scratch = &self.scratch;
+        let token = |&(start, end): &(usize, usize)| &scratch[start..end];
+        self.tokens.sort_unstable_by(|a, b| token(a).cmp(token(b)));
+        self.tokens.dedup_by(|a, b| token(a) == token(b));
+
+        out.push_str("{ ");
+        self.tokens
+            .iter()
+            .enumerate()
+            .for_each(|(position, range)| {
+                if position > 0 {
+                    out.push_str(", ");
+                }
+                out.push_str(token(range));
+            });
+        out.push_str(" }");
+    }
+}
+
+/// Renders every property as `key: value`, sorted and without duplicates.
+pub(crate) fn property_tokens(properties: &[(String, Value)]) -> Vec<String> {
+    let mut properties = properties
+        .iter()
+        .map(|(key, value)| format!("{}: {}", key, value))
+        .collect::<Vec<_>>();
+
+    properties.sort();
+    properties.dedup();
+    properties
+}
+
+fn resolve_duplicate_keys(properties: &mut Properties, policy: DuplicatePropertyKey) {
+    let mut resolved = Properties::with_capacity(properties.len());
+
+    properties.drain(..).for_each(|(key, value)| {
+        match resolved
+            .iter_mut()
+            .find(|(resolved_key, _)| *resolved_key == key)
+        {
+            Some((_, resolved_value)) => match policy {
+                DuplicatePropertyKey::First => {}
+                DuplicatePropertyKey::Last => *resolved_value = value,
+                DuplicatePropertyKey::Error => panic!(
+                    "Duplicate property key {} with values {} and {}",
+                    key, resolved_value, value
+                ),
+            },
+            None => resolved.push((key, value)),
+        }
+    });
+
+    *properties = resolved;
+}
+
+fn merge_properties(properties: Vec<Properties>, conflict: ParallelEdgeConflict) -> Properties {
+    let mut merged = Vec::<(String, Vec<Value>)>::new();
+
+    properties.into_iter().flatten().for_each(|(key, value)| {
+        match merged.iter_mut().find(|(merged_key, _)| *merged_key == key) {
+            Some((_, values)) => {
+                if !values.contains(&value) {
+                    values.push(value);
+                }
+            }
+            None => merged.push((key, vec![value])),
+        }
+    });
+
+    merged
+        .into_iter()
+        .map(|(key, mut values)| {
+            if values.len() == 1 {
+                return (key, values.remove(0));
+            }
+
+            values.sort_by(Value::canonical_cmp);
+            let value = match conflict {
+                ParallelEdgeConflict::Error => panic!(
+                    "Conflicting values for property {} on parallel relationships: {}",
+                    key,
+                    Value::List(values)
+                ),
+                ParallelEdgeConflict::List => Value::List(values),
+                ParallelEdgeConflict::Min => values.remove(0),
+                ParallelEdgeConflict::Max => values.pop().unwrap(),
+            };
+            (key, value)
+        })
+        .collect()
+}
@@ -0,0 +1,329 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug},
+};
+
+use crate::{
+    canonical::Collected,
+    graph::{Graph, LabelIterator, NodesIterator, PropertyIterator, RelationshipIterator},
+    structure::UnionFind,
+    CanonicalForm, GraphExt, Value,
+};
+
+/// A weakly connected component of a graph, see `components`.
+///
+/// The component borrows the graph and keeps its node ids. Every
+/// relationship of a node in the component ends in the component, so the
+/// component is itself a `Graph` and can be compared like any other.
+pub struct Subgraph<'a, G: Graph> {
+    graph: &'a G,
+    nodes: Vec<&'a G::NodeId>,
+    relationship_count: usize,
+}
+
+impl<G: Graph> Clone for Subgraph<'_, G> {
+    fn clone(&self) -> Self {
+        Self {
+            graph: self.graph,
+            nodes: self.nodes.clone(),
+            relationship_count: self.relationship_count,
+        }
+    }
+}
+
+impl<G: Graph> Debug for Subgraph<'_, G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subgraph")
+            .field("nodes", &self.nodes)
+            .field("relationship_count", &self.relationship_count)
+            .finish()
+    }
+}
+
+impl<G: Graph> Graph for Subgraph<'_, G> {
+    type NodeId = G::NodeId;
+
+    type NodeLabel = G::NodeLabel;
+
+    type RelationshipType = G::RelationshipType;
+
+    type PropertyKey = G::PropertyKey;
+
+    type PropertyValue = G::PropertyValue;
+
+    fn nodes(&self) -> NodesIterator<'_, &Self::NodeId> {
+        Box::new(self.nodes.iter().copied())
+    }
+
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn relationship_count(&self) -> usize {
+        self.relationship_count
+    }
+
+    fn node_labels(&self, node_id: &Self::NodeId) -> LabelIterator<'_, &Self::NodeLabel> {
+        self.graph.node_labels(node_id)
+    }
+
+    fn node_properties(
+        &self,
+        node_id: &Self::NodeId,
+    ) -> PropertyIterator<'_, &Self::PropertyKey, &Self::PropertyValue> {
+        self.graph.node_properties(node_id)
+    }
+
+    fn outgoing_relationships<'a, 'b: 'a>(
+        &'a self,
+        node_id: &'b Self::NodeId,
+    ) -> RelationshipIterator<
+        'a,
+        &'a Self::NodeId,
+        &'a Self::RelationshipType,
+        &'a Self::PropertyKey,
+        &'a Self::PropertyValue,
+    > {
+        self.graph.outgoing_relationships(node_id)
+    }
+
+    fn incoming_relationships<'a, 'b: 'a>(
+        &'a self,
+        node_id: &'b Self::NodeId,
+    ) -> RelationshipIterator<
+        'a,
+        &'a Self::NodeId,
+        &'a Self::RelationshipType,
+        &'a Self::PropertyKey,
+        &'a Self::PropertyValue,
+    > {
+        self.graph.incoming_relationships(node_id)
+    }
+
+    fn relationship_id(&self, source: &Self::NodeId, position: usize) -> Option<String> {
+        self.graph.relationship_id(source, position)
+    }
+
+    fn is_directed(&self) -> bool {
+        self.graph.is_directed()
+    }
+
+    fn property_value(&self, value: &Self::PropertyValue) -> Value {
+        self.graph.property_value(value)
+    }
+}
+
+/// Splits a graph into its weakly connected components, i.e., relationship
+/// directions are ignored for connectivity.
+///
+/// Components are ordered by their first node in `Graph::nodes`, and list
+/// their nodes in that order, too.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::{components, Graph};
+///
+/// let g = "(a:A)-->(b:B), (c:A)<--(d:B), (e)".parse::<GdlGraph>().unwrap();
+/// let components = components(&g);
+///
+/// assert_eq!(components.len(), 3);
+/// assert_eq!(components.iter().map(|c| c.node_count()).sum::<usize>(), 5);
+/// ```
+pub fn components<G: Graph>(graph: &G) -> Vec<Subgraph<'_, G>> {
+    let collected = Collected::new(graph);
+    let mut sets = UnionFind::new(collected.nodes.len());
+    collected.relationships.iter().for_each(|rel| {
+        sets.union(rel.source, rel.target);
+    });
+
+    let mut positions = HashMap::new();
+    let mut components = Vec::<Subgraph<'_, G>>::new();
+    collected.nodes.iter().enumerate().for_each(|(node, data)| {
+        let position = *positions
+            .entry(sets.find(node))
+            .or_insert_with(|| components.len());
+        if position == components.len() {
+            components.push(Subgraph {
+                graph,
+                nodes: Vec::new(),
+                relationship_count: 0,
+            });
+        }
+        components[position].nodes.push(data.id);
+    });
+    collected.relationships.iter().for_each(|rel| {
+        components[positions[&sets.find(rel.source)]].relationship_count += 1;
+    });
+
+    components
+}
+
+/// The components of two graphs paired up by canonical form, see
+/// `compare_components`.
+pub struct ComponentDiff<'a, 'b, G: Graph, H: Graph> {
+    /// The number of components that have an isomorphic counterpart.
+    pub matched: usize,
+    /// Components of the left graph without an isomorphic counterpart.
+    pub left_only: Vec<Subgraph<'a, G>>,
+    /// Components of the right graph without an isomorphic counterpart.
+    pub right_only: Vec<Subgraph<'b, H>>,
+}
+
+impl<G: Graph, H: Graph> ComponentDiff<'_, '_, G, H> {
+    /// Returns true if every component has an isomorphic counterpart, i.e.,
+    /// if both graphs are isomorphic.
+    pub fn is_empty(&self) -> bool {
+        self.left_only.is_empty() && self.right_only.is_empty()
+    }
+}
+
+impl<G: Graph, H: Graph> Debug for ComponentDiff<'_, '_, G, H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ComponentDiff")
+            .field("matched", &self.matched)
+            .field("left_only", &self.left_only)
+            .field("right_only", &self.right_only)
+            .finish()
+    }
+}
+
+/// Splits both graphs into their weakly connected components and pairs up
+/// isomorphic components.
+///
+/// Every component is matched at most once, so a component that occurs
+/// twice in the left graph but once in the right graph is reported once in
+/// `left_only`. Unmatched components keep the order of `components`.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::{compare_components, Graph};
+///
+/// let g1 = "(a:A)-->(b:B), (c:C)".parse::<GdlGraph>().unwrap();
+/// let g2 = "(x:B)<--(y:A), (z:D)".parse::<GdlGraph>().unwrap();
+///
+/// let diff = compare_components(&g1, &g2);
+/// assert_eq!(diff.matched, 1);
+/// assert_eq!(diff.left_only[0].nodes().collect::<Vec<_>>(), vec!["c"]);
+/// assert_eq!(diff.right_only[0].nodes().collect::<Vec<_>>(), vec!["z"]);
+/// ```
+pub fn compare_components<'a, 'b, G: Graph, H: Graph>(
+    left: &'a G,
+    right: &'b H,
+) -> ComponentDiff<'a, 'b, G, H> {
+    let mut unmatched = HashMap::<CanonicalForm, Vec<usize>>::new();
+    let mut left_components = components(left)
+        .into_iter()
+        .enumerate()
+        .map(|(position, component)| {
+            unmatched
+                .entry(component.canonical())
+                .or_default()
+                .push(position);
+            Some(component)
+        })
+        .collect::<Vec<_>>();
+    unmatched
+        .values_mut()
+        .for_each(|positions| positions.reverse());
+
+    let mut matched = 0;
+    let mut right_only = Vec::new();
+    components(right).into_iter().for_each(|component| {
+        match unmatched.get_mut(&component.canonical()).and_then(Vec::pop) {
+            Some(position) => {
+                left_components[position] = None;
+                matched += 1;
+            }
+            None => right_only.push(component),
+        }
+    });
+
+    ComponentDiff {
+        matched,
+        left_only: left_components.into_iter().flatten().collect(),
+        right_only,
+    }
+}
+
+#[cfg(all(feature = "gdl", test))]
+mod tests {
+    use super::*;
+    use crate::{canonicalize, equals};
+
+    use ::gdl::Graph as GdlGraph;
+
+    fn from_gdl(gdl: &str) -> GdlGraph {
+        gdl.parse::<GdlGraph>().unwrap()
+    }
+
+    fn sorted_nodes<'a, G: Graph<NodeId = str>>(component: &'a Subgraph<'_, G>) -> Vec<&'a str> {
+        let mut nodes = component.nodes().collect::<Vec<_>>();
+        nodes.sort_unstable();
+        nodes
+    }
+
+    #[test]
+    fn test_components() {
+        let g = from_gdl("(a:A)-[:R]->(b:B)<-[:R]-(c:A), (d:C)-[:S]->(d), (e:D)");
+        let mut components = components(&g);
+        components.sort_by(|a, b| sorted_nodes(a).cmp(&sorted_nodes(b)));
+
+        assert_eq!(
+            components.iter().map(sorted_nodes).collect::<Vec<_>>(),
+            vec![vec!["a", "b", "c"], vec!["d"], vec!["e"]]
+        );
+        assert_eq!(
+            components
+                .iter()
+                .map(|component| (component.node_count(), component.relationship_count()))
+                .collect::<Vec<_>>(),
+            vec![(3, 2), (1, 1), (1, 0)]
+        );
+        assert!(equals(
+            &components[0],
+            &from_gdl("(x:A)-[:R]->(y:B)<-[:R]-(z:A)")
+        ));
+        assert_eq!(
+            canonicalize(&components[1]),
+            canonicalize(&from_gdl("(x:C)-[:S]->(x)"))
+        );
+    }
+
+    #[test]
+    fn test_components_of_empty_graph() {
+        assert!(components(&crate::SimpleGraph::new()).is_empty());
+    }
+
+    #[test]
+    fn test_compare_components() {
+        let left =
+            from_gdl("(a:A)-[:R]->(b:B), (c:C)-[:S]->(d:C)-[:S]->(c), (e:E {v: 1})-[:T]->(f:F)");
+        let right =
+            from_gdl("(x:C)-[:S]->(y:C)-[:S]->(x), (z:E {v: 2})-[:T]->(w:F), (u:B)<-[:R]-(v:A)");
+
+        let diff = compare_components(&left, &right);
+
+        assert_eq!(diff.matched, 2);
+        assert!(!diff.is_empty());
+        assert_eq!(diff.left_only.len(), 1);
+        assert_eq!(diff.right_only.len(), 1);
+        assert_eq!(sorted_nodes(&diff.left_only[0]), vec!["e", "f"]);
+        assert_eq!(sorted_nodes(&diff.right_only[0]), vec!["w", "z"]);
+
+        assert!(compare_components(&left, &left).is_empty());
+    }
+
+    #[test]
+    fn test_compare_components_counts_duplicates() {
+        let left = from_gdl("(a:A), (b:A), (c:B)");
+        let right = from_gdl("(x:A), (y:B), (z:B)");
+
+        let diff = compare_components(&left, &right);
+
+        assert_eq!(diff.matched, 2);
+        assert_eq!(diff.left_only.len(), 1);
+        assert_eq!(diff.right_only.len(), 1);
+        assert_eq!(canonicalize(&diff.left_only[0]), "(:A ) => out:  in: ");
+        assert_eq!(canonicalize(&diff.right_only[0]), "(:B ) => out:  in: ");
+    }
+}
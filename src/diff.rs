@@ -0,0 +1,567 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display},
+};
+
+use crate::{canonical, CompareOptions, Graph};
+
+/// Canonical tokens with their multiplicities.
+pub type TokenCounts = Vec<(String, usize)>;
+
+/// The canonical nodes and relationships that differ between two graphs.
+///
+/// Every entry is a canonical token together with the number of times it
+/// was added or removed. Nodes are rendered as in the canonical matrix,
+/// relationships as `(source)-[:TYPE { properties }]->(target)`.
+/// Entries are sorted by token.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StructuralDelta {
+    pub added_nodes: TokenCounts,
+    pub removed_nodes: TokenCounts,
+    pub added_edges: TokenCounts,
+    pub removed_edges: TokenCounts,
+}
+
+impl StructuralDelta {
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+    }
+}
+
+/// Computes which canonical nodes and relationships have to be added to and
+/// removed from `base` to arrive at `target`.
+///
+/// An empty delta does not imply that both graphs are isomorphic, since the
+/// tokens do not capture how relationships are wired to specific nodes.
+pub fn structural_delta(base: &impl Graph, target: &impl Graph) -> StructuralDelta {
+    let options = CompareOptions::default();
+    let base = canonical::collect(base, &options);
+    let target = canonical::collect(target, &options);
+
+    let base_nodes = base.canonical_nodes();
+    let target_nodes = target.canonical_nodes();
+    let base_edges = base.canonical_relationships(&base_nodes);
+    let target_edges = target.canonical_relationships(&target_nodes);
+
+    let (added_nodes, removed_nodes) = multiset_difference(target_nodes, base_nodes);
+    let (added_edges, removed_edges) = multiset_difference(target_edges, base_edges);
+
+    StructuralDelta {
+        added_nodes,
+        removed_nodes,
+        added_edges,
+        removed_edges,
+    }
+}
+
+/// A canonical row that differs between two graphs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mismatch {
+    /// A row that only occurs in the left graph.
+    LeftOnly { row: String },
+    /// A row that only occurs in the right graph.
+    RightOnly { row: String },
+    /// A node that occurs in both graphs, but with different relationships.
+    Changed { left: String, right: String },
+}
+
+/// The canonical rows that differ between two graphs, see `graph_diff`.
+///
+/// The `Display` implementation renders a unified diff, prefixing rows of
+/// the left graph with `-` and rows of the right graph with `+`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GraphDiff {
+    mismatches: Vec<Mismatch>,
+}
+
+impl GraphDiff {
+    pub fn is_empty(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    pub fn mismatches(&self) -> &[Mismatch] {
+        &self.mismatches
+    }
+
+    pub fn into_mismatches(self) -> Vec<Mismatch> {
+        self.mismatches
+    }
+}
+
+impl Display for GraphDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.mismatches
+            .iter()
+            .try_for_each(|mismatch| match mismatch {
+                Mismatch::LeftOnly { row } => writeln!(f, "- {}", row),
+                Mismatch::RightOnly { row } => writeln!(f, "+ {}", row),
+                Mismatch::Changed { left, right } => writeln!(f, "- {}\n+ {}", left, right),
+            })
+    }
+}
+
+#[cfg(feature = "color")]
+impl GraphDiff {
+    /// Renders the diff like `Display`, but colors rows of the left graph red
+    /// and rows of the right graph green. For changed nodes, the part of the
+    /// rows that differs between both graphs is also underlined.
+    ///
+    /// Colors are left out if the `NO_COLOR` environment variable is set or if
+    /// stderr, where failed assertions are reported, is not a terminal.
+    pub fn to_colored_string(&self) -> String {
+        use std::io::IsTerminal;
+
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        if no_color || !std::io::stderr().is_terminal() {
+            self.to_string()
+        } else {
+            self.render_colored()
+        }
+    }
+
+    fn render_colored(&self) -> String {
+        use anstyle::{AnsiColor, Style};
+
+        const LEFT: Style = AnsiColor::Red.on_default();
+        const RIGHT: Style = AnsiColor::Green.on_default();
+
+        let line = |style: Style, sign: char, row: &str| format!("{style}{sign} {row}{style:#}\n");
+        let highlighted =
+            |style: Style, sign: char, (prefix, changed, suffix): (&str, &str, &str)| {
+                let highlight = style.underline();
+                format!(
+                "{style}{sign} {prefix}{highlight}{changed}{highlight:#}{style}{suffix}{style:#}\n"
+            )
+            };
+
+        self.mismatches
+            .iter()
+            .map(|mismatch| match mismatch {
+                Mismatch::LeftOnly { row } => line(LEFT, '-', row),
+                Mismatch::RightOnly { row } => line(RIGHT, '+', row),
+                Mismatch::Changed { left, right } => {
+                    let (left, right) = changed_segments(left, right);
+                    highlighted(LEFT, '-', left) + &highlighted(RIGHT, '+', right)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Splits both rows into their common prefix, the segment that differs and
+/// their common suffix.
+#[cfg(feature = "color")]
+fn changed_segments<'a>(
+    left: &'a str,
+    right: &'a str,
+) -> ((&'a str, &'a str, &'a str), (&'a str, &'a str, &'a str)) {
+    let prefix = left
+        .char_indices()
+        .zip(right.chars())
+        .find(|((_, l), r)| l != r)
+        .map_or(left.len().min(right.len()), |((position, _), _)| position);
+    let suffix = left[prefix..]
+        .chars()
+        .rev()
+        .zip(right[prefix..].chars().rev())
+        .take_while(|(l, r)| l == r)
+        .map(|(c, _)| c.len_utf8())
+        .sum::<usize>();
+
+    let split = |row: &'a str| {
+        let end = row.len() - suffix;
+        (&row[..prefix], &row[prefix..end], &row[end..])
+    };
+    (split(left), split(right))
+}
+
+/// Compares the canonical rows of both graphs.
+///
+/// Rows that only occur in one graph are paired up if they describe the
+/// same node, i.e., they only differ in their relationships, and reported
+/// as changed. All other rows, e.g. if both graphs have a different number
+/// of nodes, are reported as only occurring in the left or right graph.
+/// Mismatches are sorted by node.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::*;
+///
+/// let g1 = "(a:A), (b:B), (a)-->(b)".parse::<GdlGraph>().unwrap();
+/// let g2 = "(a:A), (b:B), (c:C), (b)-->(a)".parse::<GdlGraph>().unwrap();
+///
+/// let diff = graph_diff(&g1, &g2);
+/// assert_eq!(diff.mismatches().len(), 3);
+/// assert!(graph_diff(&g1, &g1).is_empty());
+/// ```
+pub fn graph_diff(left: &impl Graph, right: &impl Graph) -> GraphDiff {
+    graph_diff_with(left, right, &CompareOptions::default())
+}
+
+/// Like `graph_diff`, but canonicalizes both graphs as `canonicalize_with` does.
+pub fn graph_diff_with(
+    left: &impl Graph,
+    right: &impl Graph,
+    options: &CompareOptions,
+) -> GraphDiff {
+    let mut by_node = BTreeMap::<String, (Vec<String>, Vec<String>)>::new();
+    let node = |row: &str| row.split(" => ").next().unwrap_or_default().to_string();
+
+    aligned_rows_with(left, right, options)
+        .into_iter()
+        .for_each(|row| match row {
+            AlignedRow::Shared(_) => {}
+            AlignedRow::Removed(row) => by_node.entry(node(&row)).or_default().0.push(row),
+            AlignedRow::Added(row) => by_node.entry(node(&row)).or_default().1.push(row),
+        });
+
+    let mut mismatches = Vec::new();
+    by_node.into_values().for_each(|(removed, added)| {
+        let mut removed = removed.into_iter();
+        let mut added = added.into_iter();
+        loop {
+            let mismatch = match (removed.next(), added.next()) {
+                (Some(left), Some(right)) => Mismatch::Changed { left, right },
+                (Some(row), None) => Mismatch::LeftOnly { row },
+                (None, Some(row)) => Mismatch::RightOnly { row },
+                (None, None) => break,
+            };
+            mismatches.push(mismatch);
+        }
+    });
+
+    GraphDiff { mismatches }
+}
+
+/// Scores how similar two graphs are as a value between `0` and `1`,
+/// weighting nodes and relationships equally.
+///
+/// See `weighted_similarity` for the formula.
+pub fn similarity(left: &impl Graph, right: &impl Graph) -> f64 {
+    weighted_similarity(left, right, 1.0, 1.0)
+}
+
+/// Scores how similar two graphs are as a value between `0` and `1`.
+///
+/// Nodes and relationships are compared as multisets of the canonical
+/// tokens used by `structural_delta`. For each multiset, the overlap is the
+/// Jaccard index `|L ∩ R| / |L ∪ R|`, or `1` if both are empty. The score is
+///
+/// ```text
+/// (node_weight * node_overlap + edge_weight * edge_overlap) / (node_weight + edge_weight)
+/// ```
+///
+/// Isomorphic graphs score `1`, but a score of `1` does not imply that the
+/// graphs are isomorphic, see `structural_delta`.
+///
+/// Panics if a weight is negative or both weights are zero.
+pub fn weighted_similarity(
+    left: &impl Graph,
+    right: &impl Graph,
+    node_weight: f64,
+    edge_weight: f64,
+) -> f64 {
+    assert!(
+        node_weight >= 0.0 && edge_weight >= 0.0 && node_weight + edge_weight > 0.0,
+        "Weights must be non-negative and not both zero, got {} and {}",
+        node_weight,
+        edge_weight
+    );
+
+    let options = CompareOptions::default();
+    let left = canonical::collect(left, &options);
+    let right = canonical::collect(right, &options);
+
+    let left_nodes = left.canonical_nodes();
+    let right_nodes = right.canonical_nodes();
+    let left_edges = left.canonical_relationships(&left_nodes);
+    let right_edges = right.canonical_relationships(&right_nodes);
+
+    let node_overlap = jaccard(left_nodes, right_nodes);
+    let edge_overlap = jaccard(left_edges, right_edges);
+
+    (node_weight * node_overlap + edge_weight * edge_overlap) / (node_weight + edge_weight)
+}
+
+/// The Jaccard index of two multisets, `1` if both are empty.
+fn jaccard(left: Vec<String>, right: Vec<String>) -> f64 {
+    let total = left.len() + right.len();
+    if total == 0 {
+        return 1.0;
+    }
+    let (left_only, right_only) = multiset_difference(left, right);
+    let different = left_only
+        .into_iter()
+        .chain(right_only)
+        .map(|(_, count)| count)
+        .sum::<usize>();
+    let shared = (total - different) / 2;
+    shared as f64 / (shared + different) as f64
+}
+
+/// A canonical row in the alignment of two canonical matrices.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum AlignedRow {
+    Shared(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Aligns the sorted canonical rows of `left` and `right`. Rows only in
+/// `left` are removed, rows only in `right` are added.
+pub(crate) fn aligned_rows(left: &impl Graph, right: &impl Graph) -> Vec<AlignedRow> {
+    aligned_rows_with(left, right, &CompareOptions::default())
+}
+
+pub(crate) fn aligned_rows_with(
+    left: &impl Graph,
+    right: &impl Graph,
+    options: &CompareOptions,
+) -> Vec<AlignedRow> {
+    let mut left = canonical::collect(left, options).rows();
+    let mut right = canonical::collect(right, options).rows();
+    left.sort();
+    right.sort();
+
+    let mut aligned = Vec::with_capacity(left.len().max(right.len()));
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+
+    loop {
+        let row = match (left.peek(), right.peek()) {
+            (Some(l), Some(r)) if l == r => {
+                right.next();
+                AlignedRow::Shared(left.next().unwrap())
+            }
+            (Some(l), Some(r)) if l < r => AlignedRow::Removed(left.next().unwrap()),
+            (Some(_), None) => AlignedRow::Removed(left.next().unwrap()),
+            (_, Some(_)) => AlignedRow::Added(right.next().unwrap()),
+            (None, None) => break,
+        };
+        aligned.push(row);
+    }
+
+    aligned
+}
+
+/// Renders the aligned rows of both graphs, prefixing shared rows with
+/// two spaces, removed rows with `- ` and added rows with `+ `.
+pub(crate) fn format_row_diff(left: &impl Graph, right: &impl Graph) -> String {
+    aligned_rows(left, right)
+        .into_iter()
+        .map(|row| match row {
+            AlignedRow::Shared(row) => format!("  {}", row),
+            AlignedRow::Removed(row) => format!("- {}", row),
+            AlignedRow::Added(row) => format!("+ {}", row),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns the tokens that occur more often in `left` and those that occur
+/// more often in `right`, each with the surplus count.
+pub(crate) fn multiset_difference(
+    left: Vec<String>,
+    right: Vec<String>,
+) -> (TokenCounts, TokenCounts) {
+    let mut counts = HashMap::<String, isize>::new();
+    left.into_iter()
+        .for_each(|token| *counts.entry(token).or_default() += 1);
+    right
+        .into_iter()
+        .for_each(|token| *counts.entry(token).or_default() -= 1);
+
+    let mut left_only = Vec::new();
+    let mut right_only = Vec::new();
+    counts.into_iter().for_each(|(token, count)| {
+        if count > 0 {
+            left_only.push((token, count as usize));
+        } else if count < 0 {
+            right_only.push((token, count.unsigned_abs()));
+        }
+    });
+
+    left_only.sort();
+    right_only.sort();
+    (left_only, right_only)
+}
+
+#[cfg(all(feature = "gdl", test))]
+mod tests {
+    use super::*;
+    use crate::SimpleGraph;
+
+    use ::gdl::Graph as GdlGraph;
+
+    fn from_gdl(gdl: &str) -> GdlGraph {
+        gdl.parse::<GdlGraph>().unwrap()
+    }
+
+    #[test]
+    fn test_structural_delta() {
+        let base = from_gdl("(a:A), (b:B), (a)-[:REL]->(b), (a)-[:REL]->(b), (b)-[:REL]->(a)");
+        let target = from_gdl("(a:A), (b:B), (c:C), (a)-[:REL]->(b), (b)-[:REL]->(a)");
+
+        assert_eq!(
+            structural_delta(&base, &target),
+            StructuralDelta {
+                added_nodes: vec![("(:C )".to_string(), 1)],
+                removed_nodes: vec![],
+                added_edges: vec![],
+                removed_edges: vec![("(:A )-[:REL ]->(:B )".to_string(), 1)],
+            }
+        );
+    }
+
+    #[test]
+    fn test_structural_delta_of_isomorphic_graphs_is_empty() {
+        let base = from_gdl("(a:A), (b:B), (a)-[:REL {w: 1}]->(b)");
+        let target = from_gdl("(x:B), (y:A), (y)-[:REL {w: 1}]->(x)");
+
+        assert!(structural_delta(&base, &target).is_empty());
+    }
+
+    #[test]
+    fn test_aligned_rows() {
+        let left = from_gdl("(a:A), (b:B)");
+        let right = from_gdl("(b:B), (c:C)");
+
+        assert_eq!(
+            aligned_rows(&left, &right),
+            vec![
+                AlignedRow::Removed("(:A ) => out:  in: ".to_string()),
+                AlignedRow::Shared("(:B ) => out:  in: ".to_string()),
+                AlignedRow::Added("(:C ) => out:  in: ".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_similarity_of_isomorphic_graphs() {
+        let g1 = from_gdl("(a:A), (b:B), (a)-[:REL]->(b)");
+        let g2 = from_gdl("(x:B), (y:A), (y)-[:REL]->(x)");
+
+        assert_eq!(similarity(&g1, &g2), 1.0);
+        assert_eq!(similarity(&SimpleGraph::new(), &SimpleGraph::new()), 1.0);
+    }
+
+    #[test]
+    fn test_weighted_similarity() {
+        let base = from_gdl("(a:A), (b:B), (a)-[:REL]->(b), (b)-[:REL]->(a)");
+        // same nodes, one of two relationships differs
+        let edges_differ = from_gdl("(a:A), (b:B), (a)-[:REL]->(b), (a)-[:REL]->(b)");
+        // same relationships, one of three nodes differs
+        let nodes_differ = from_gdl("(a:A), (b:B), (c:C), (a)-[:REL]->(b), (b)-[:REL]->(a)");
+
+        assert_eq!(similarity(&base, &edges_differ), (1.0 + 1.0 / 3.0) / 2.0);
+        assert_eq!(similarity(&base, &nodes_differ), (2.0 / 3.0 + 1.0) / 2.0);
+
+        let edge_heavy = |graph| weighted_similarity(&base, graph, 1.0, 3.0);
+        assert!(edge_heavy(&edges_differ) < similarity(&base, &edges_differ));
+        assert!(edge_heavy(&nodes_differ) > similarity(&base, &nodes_differ));
+        assert_eq!(weighted_similarity(&base, &edges_differ, 1.0, 0.0), 1.0);
+        assert_eq!(weighted_similarity(&base, &nodes_differ, 0.0, 1.0), 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Weights must be non-negative")]
+    fn test_weighted_similarity_rejects_zero_weights() {
+        let g = from_gdl("(a)");
+        weighted_similarity(&g, &g, 0.0, 0.0);
+    }
+
+    #[test]
+    fn test_graph_diff() {
+        let left = from_gdl("(a:A), (b:B), (d:D), (a)-->(b)");
+        let right = from_gdl("(a:A), (b:B), (c:C), (b)-->(a)");
+
+        let diff = graph_diff(&left, &right);
+
+        assert_eq!(
+            diff.clone().into_mismatches(),
+            vec![
+                Mismatch::Changed {
+                    left: "(:A ) => out: ()-[: ]->(:B ) in: ".to_string(),
+                    right: "(:A ) => out:  in: ()<-[: ]-(:B )".to_string(),
+                },
+                Mismatch::Changed {
+                    left: "(:B ) => out:  in: ()<-[: ]-(:A )".to_string(),
+                    right: "(:B ) => out: ()-[: ]->(:A ) in: ".to_string(),
+                },
+                Mismatch::RightOnly {
+                    row: "(:C ) => out:  in: ".to_string()
+                },
+                Mismatch::LeftOnly {
+                    row: "(:D ) => out:  in: ".to_string()
+                },
+            ]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "- (:A ) => out: ()-[: ]->(:B ) in: \n\
+             + (:A ) => out:  in: ()<-[: ]-(:B )\n\
+             - (:B ) => out:  in: ()<-[: ]-(:A )\n\
+             + (:B ) => out: ()-[: ]->(:A ) in: \n\
+             + (:C ) => out:  in: \n\
+             - (:D ) => out:  in: \n"
+        );
+    }
+
+    #[test]
+    fn test_graph_diff_of_different_node_counts() {
+        let left = from_gdl("(a:A), (b:A)");
+        let right = from_gdl("(a:A), (b:A), (c:A), (a)-->(b)");
+
+        assert_eq!(
+            graph_diff(&left, &right).into_mismatches(),
+            vec![
+                Mismatch::Changed {
+                    left: "(:A ) => out:  in: ".to_string(),
+                    right: "(:A ) => out:  in: ()<-[: ]-(:A )".to_string(),
+                },
+                Mismatch::RightOnly {
+                    row: "(:A ) => out: ()-[: ]->(:A ) in: ".to_string()
+                },
+            ]
+        );
+        assert!(graph_diff(&left, &left).is_empty());
+    }
+
+    #[cfg(feature = "color")]
+    #[test]
+    fn test_changed_segments() {
+        assert_eq!(
+            changed_segments("(:A ) => out: x in: ", "(:A ) => out: yz in: "),
+            (
+                ("(:A ) => out: ", "x", " in: "),
+                ("(:A ) => out: ", "yz", " in: ")
+            )
+        );
+        assert_eq!(
+            changed_segments("(:A ) => out:  in: ", "(:A ) => out:  in: x"),
+            (
+                ("(:A ) => out:  in: ", "", ""),
+                ("(:A ) => out:  in: ", "x", "")
+            )
+        );
+    }
+
+    #[cfg(feature = "color")]
+    #[test]
+    fn test_colored_graph_diff() {
+        let left = from_gdl("(a:A), (b:B), (c:C), (a)-[:R]->(b)");
+        let right = from_gdl("(a:A), (b:B), (a)-[:S]->(b)");
+
+        assert_eq!(
+            graph_diff(&left, &right).render_colored(),
+            "\u{1b}[31m- (:A ) => out: ()-[:\u{1b}[4m\u{1b}[31mR\u{1b}[0m\u{1b}[31m ]->(:B ) in: \u{1b}[0m\n\
+             \u{1b}[32m+ (:A ) => out: ()-[:\u{1b}[4m\u{1b}[32mS\u{1b}[0m\u{1b}[32m ]->(:B ) in: \u{1b}[0m\n\
+             \u{1b}[31m- (:B ) => out:  in: ()<-[:\u{1b}[4m\u{1b}[31mR\u{1b}[0m\u{1b}[31m ]-(:A )\u{1b}[0m\n\
+             \u{1b}[32m+ (:B ) => out:  in: ()<-[:\u{1b}[4m\u{1b}[32mS\u{1b}[0m\u{1b}[32m ]-(:A )\u{1b}[0m\n\
+             \u{1b}[31m- (:C ) => out:  in: \u{1b}[0m\n"
+        );
+    }
+}
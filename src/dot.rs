@@ -0,0 +1,153 @@
+use std::{collections::HashMap, fmt::Write};
+
+use crate::{
+    canonical::{self, property_tokens},
+    node_orbits, CompareOptions, Graph,
+};
+
+/// Fill colors for orbits, assigned in orbit order and repeated if there
+/// are more orbits than colors.
+const PALETTE: [&str; 12] = [
+    "#8dd3c7", "#ffffb3", "#bebada", "#fb8072", "#80b1d3", "#fdb462", "#b3de69", "#fccde5",
+    "#d9d9d9", "#bc80bd", "#ccebc5", "#ffed6f",
+];
+
+/// Renders the graph in the DOT format of Graphviz.
+///
+/// Nodes are named `n0`, `n1`, ... by their position in `graph.nodes()`
+/// and labeled with their canonical form, relationships are labeled with
+/// their type and properties.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::to_dot;
+///
+/// let g = "(a:A), (b:B), (a)-[:REL { w: 1 }]->(b)".parse::<GdlGraph>().unwrap();
+///
+/// let dot = to_dot(&g);
+/// assert!(dot.starts_with("digraph {"));
+/// assert!(dot.contains(" [label=\":REL { w: 1 }\"];"));
+/// ```
+pub fn to_dot<G: Graph>(graph: &G) -> String {
+    render(graph, None)
+}
+
+/// Like `to_dot`, but fills all nodes of an orbit with the same color.
+///
+/// Orbits are computed by `node_orbits` and get their color by their
+/// position in the sorted orbits, so isomorphic graphs are colored alike.
+pub fn to_dot_colored_by_orbit<G: Graph>(graph: &G) -> String {
+    let positions = graph
+        .nodes()
+        .enumerate()
+        .map(|(position, node)| (node, position))
+        .collect::<HashMap<_, _>>();
+
+    let mut colors = vec![""; positions.len()];
+    node_orbits(graph)
+        .into_iter()
+        .enumerate()
+        .for_each(|(orbit, nodes)| {
+            nodes
+                .into_iter()
+                .for_each(|node| colors[positions[node]] = PALETTE[orbit % PALETTE.len()])
+        });
+
+    render(graph, Some(&colors))
+}
+
+fn render<G: Graph>(graph: &G, colors: Option<&[&str]>) -> String {
+    let graph = canonical::collect(graph, &CompareOptions::default());
+    let nodes = graph.canonical_nodes();
+
+    let mut dot = String::from("digraph {\n");
+
+    nodes.iter().enumerate().for_each(|(node, label)| {
+        write!(dot, "    n{} [label=\"{}\"", node, escape(label)).unwrap();
+        if let Some(colors) = colors {
+            write!(dot, ", style=filled, fillcolor=\"{}\"", colors[node]).unwrap();
+        }
+        dot.push_str("];\n");
+    });
+
+    graph.relationships.iter().for_each(|rel| {
+        let properties = property_tokens(&rel.properties).join(", ");
+        let label = if properties.is_empty() {
+            format!(":{}", rel.rel_type)
+        } else {
+            format!(":{} {{ {} }}", rel.rel_type, properties)
+        };
+        writeln!(
+            dot,
+            "    n{} -> n{} [label=\"{}\"];",
+            rel.source,
+            rel.target,
+            escape(&label)
+        )
+        .unwrap();
+    });
+
+    dot.push_str("}\n");
+    dot
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(all(feature = "gdl", test))]
+mod tests {
+    use super::*;
+
+    use ::gdl::Graph as GdlGraph;
+
+    fn from_gdl(gdl: &str) -> GdlGraph {
+        gdl.parse::<GdlGraph>().unwrap()
+    }
+
+    /// Returns the fill color of every node, ordered by node name.
+    fn fill_colors(graph: &GdlGraph) -> Vec<String> {
+        let mut nodes = Graph::nodes(graph).enumerate().collect::<Vec<_>>();
+        nodes.sort_by_key(|(_, node)| *node);
+
+        let dot = to_dot_colored_by_orbit(graph);
+        nodes
+            .into_iter()
+            .map(|(position, _)| {
+                let line = dot
+                    .lines()
+                    .find(|line| line.starts_with(&format!("    n{} [", position)))
+                    .unwrap();
+                line.split("fillcolor=\"").nth(1).unwrap()[..7].to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let g = from_gdl(r#"(a:A { name: "x\"y" }), (a)-[:REL]->(a)"#);
+
+        assert_eq!(
+            to_dot(&g),
+            "digraph {\n    n0 [label=\"(:A { name: x\\\\\\\"y })\"];\n    n0 -> n0 [label=\":REL\"];\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_cycle_has_a_single_color() {
+        let colors = fill_colors(&from_gdl("(a)-->(b)-->(c)-->(d)-->(a)"));
+
+        assert_eq!(colors, vec![PALETTE[0]; 4]);
+    }
+
+    #[test]
+    fn test_path_is_colored_symmetrically() {
+        let colors = fill_colors(&from_gdl("(a)-->(b)-->(c)<--(d)<--(e)"));
+
+        assert_eq!(colors[0], colors[4]);
+        assert_eq!(colors[1], colors[3]);
+        assert_ne!(colors[0], colors[1]);
+        assert_ne!(colors[1], colors[2]);
+        assert_ne!(colors[0], colors[2]);
+    }
+}
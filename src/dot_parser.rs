@@ -0,0 +1,607 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    iter::Peekable,
+    str::Chars,
+};
+
+use crate::{SimpleGraph, Value};
+
+/// A DOT document that could not be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DotError {
+    /// The line on which parsing failed, starting at 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for DotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid DOT in line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for DotError {}
+
+/// Parses a graph in the DOT format of Graphviz.
+///
+/// A `digraph` yields a directed graph, a `graph` an undirected one, see
+/// `Graph::is_directed`. Every node is identified by its DOT id, nodes that
+/// only occur in edges are created with the default attributes. The `label`
+/// attribute becomes the label of a node or the type of a relationship, all
+/// other attributes become properties. Unquoted numerals are read as
+/// numbers, all other attribute values as strings. Default attributes set
+/// by `node [...]` apply to all nodes created later, also by an edge, and
+/// those set by `edge [...]` to all later edges. Graph attributes are ignored.
+///
+/// Subgraphs, ports and HTML strings are not supported, `strict` is ignored.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::{equals, parse_dot};
+///
+/// let dot = parse_dot(r#"
+///     digraph {
+///         a [label=Person, name="Alice"];
+///         a -> b [label=KNOWS, since=2020];
+///     }
+/// "#)
+/// .unwrap();
+///
+/// let expected = "(a:Person { name: 'Alice' }), (b), (a)-[:KNOWS { since: 2020 }]->(b)"
+///     .parse::<GdlGraph>()
+///     .unwrap();
+///
+/// assert!(equals(&dot, &expected));
+/// ```
+pub fn parse_dot(input: &str) -> Result<SimpleGraph, DotError> {
+    Parser::new(input)?.graph()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Id { id: String, quoted: bool },
+    Open,
+    Close,
+    OpenAttributes,
+    CloseAttributes,
+    Equals,
+    Separator,
+    Edge { directed: bool },
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Id { id, .. } => write!(f, "{:?}", id),
+            Token::Open => write!(f, "'{{'"),
+            Token::Close => write!(f, "'}}'"),
+            Token::OpenAttributes => write!(f, "'['"),
+            Token::CloseAttributes => write!(f, "']'"),
+            Token::Equals => write!(f, "'='"),
+            Token::Separator => write!(f, "';'"),
+            Token::Edge { directed: true } => write!(f, "'->'"),
+            Token::Edge { directed: false } => write!(f, "'--'"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, DotError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    let mut line = 1;
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '\n' => {
+                line += 1;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            '#' => {
+                skip_line(&mut chars);
+                line += 1;
+                continue;
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                skip_line(&mut chars);
+                line += 1;
+                continue;
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == '*' => break,
+                        Some(c) => {
+                            line += usize::from(c == '\n');
+                            previous = c;
+                        }
+                        None => return Err(error(line, "unterminated comment")),
+                    }
+                }
+                continue;
+            }
+            '{' => Token::Open,
+            '}' => Token::Close,
+            '[' => Token::OpenAttributes,
+            ']' => Token::CloseAttributes,
+            '=' => Token::Equals,
+            ';' | ',' => Token::Separator,
+            '-' if matches!(chars.peek(), Some('>') | Some('-')) => Token::Edge {
+                directed: chars.next() == Some('>'),
+            },
+            '"' => {
+                let mut id = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') if chars.peek() == Some(&'"') => id.push(chars.next().unwrap()),
+                        Some(c) => {
+                            line += usize::from(c == '\n');
+                            id.push(c);
+                        }
+                        None => return Err(error(line, "unterminated string")),
+                    }
+                }
+                Token::Id { id, quoted: true }
+            }
+            '<' => return Err(error(line, "HTML strings are not supported")),
+            ':' => return Err(error(line, "ports are not supported")),
+            c if is_id_char(c) || c == '-' || c == '.' => {
+                let mut id = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if !is_id_char(c) && c != '.' {
+                        break;
+                    }
+                    id.push(c);
+                    chars.next();
+                }
+                Token::Id { id, quoted: false }
+            }
+            c => return Err(error(line, format!("unexpected character {:?}", c))),
+        };
+        tokens.push((token, line));
+    }
+
+    Ok(tokens)
+}
+
+fn skip_line(chars: &mut Peekable<Chars<'_>>) {
+    for c in chars {
+        if c == '\n' {
+            break;
+        }
+    }
+}
+
+fn is_id_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn error(line: usize, message: impl Into<String>) -> DotError {
+    DotError {
+        line,
+        message: message.into(),
+    }
+}
+
+type Attributes = Vec<(String, Value)>;
+
+#[derive(Default)]
+struct NodeData {
+    labels: Vec<String>,
+    properties: Attributes,
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    directed: bool,
+    nodes: Vec<(String, NodeData)>,
+    index: HashMap<String, usize>,
+    edges: Vec<(String, String, String, Attributes)>,
+    node_defaults: Attributes,
+    edge_defaults: Attributes,
+}
+
+impl Parser {
+    fn new(input: &str) -> Result<Self, DotError> {
+        Ok(Self {
+            tokens: tokenize(input)?,
+            position: 0,
+            directed: true,
+            nodes: Vec::new(),
+            index: HashMap::new(),
+            edges: Vec::new(),
+            node_defaults: Vec::new(),
+            edge_defaults: Vec::new(),
+        })
+    }
+
+    fn graph(mut self) -> Result<SimpleGraph, DotError> {
+        if self.keyword("strict") {
+            self.position += 1;
+        }
+        self.directed = if self.keyword("digraph") {
+            true
+        } else if self.keyword("graph") {
+            false
+        } else {
+            return Err(self.unexpected("'graph' or 'digraph'"));
+        };
+        self.position += 1;
+
+        if let Some(Token::Id { .. }) = self.peek() {
+            self.position += 1;
+        }
+        self.expect(Token::Open, "'{'")?;
+
+        while self.peek() != Some(&Token::Close) {
+            self.statement()?;
+        }
+        self.position += 1;
+
+        if self.peek().is_some() {
+            return Err(self.unexpected("the end of the input"));
+        }
+
+        Ok(self.build())
+    }
+
+    fn statement(&mut self) -> Result<(), DotError> {
+        if self.keyword("subgraph") || self.peek() == Some(&Token::Open) {
+            return Err(error(self.line(), "subgraphs are not supported"));
+        }
+
+        if self.keyword("node") || self.keyword("edge") || self.keyword("graph") {
+            let target = self.id()?.0.to_lowercase();
+            let attributes = self.attribute_lists()?;
+            match target.as_str() {
+                "node" => self.node_defaults.extend(attributes),
+                "edge" => self.edge_defaults.extend(attributes),
+                _ => {}
+            }
+        } else {
+            let mut ids = vec![self.id()?.0];
+            if self.peek() == Some(&Token::Equals) {
+                // A graph attribute, such as `rankdir = LR`.
+                self.position += 1;
+                self.id()?;
+            } else {
+                while let Some(&Token::Edge { directed }) = self.peek() {
+                    if directed != self.directed {
+                        return Err(error(
+                            self.line(),
+                            if self.directed {
+                                "'--' in a directed graph"
+                            } else {
+                                "'->' in an undirected graph"
+                            },
+                        ));
+                    }
+                    self.position += 1;
+                    ids.push(self.id()?.0);
+                }
+
+                let attributes = self.attribute_lists()?;
+                if ids.len() == 1 {
+                    let node = self.node(ids.pop().unwrap());
+                    attributes
+                        .into_iter()
+                        .for_each(|(key, value)| set_node_attribute(node, key, value));
+                } else {
+                    ids.iter().for_each(|id| {
+                        self.node(id.clone());
+                    });
+                    ids.windows(2).for_each(|pair| {
+                        let mut rel_type = String::new();
+                        let mut properties = Attributes::new();
+                        self.edge_defaults
+                            .iter()
+                            .cloned()
+                            .chain(attributes.iter().cloned())
+                            .for_each(|(key, value)| {
+                                if key == "label" {
+                                    rel_type = value.to_string();
+                                } else {
+                                    set(&mut properties, key, value);
+                                }
+                            });
+                        self.edges
+                            .push((pair[0].clone(), pair[1].clone(), rel_type, properties));
+                    });
+                }
+            }
+        }
+
+        if self.peek() == Some(&Token::Separator) {
+            self.position += 1;
+        }
+        Ok(())
+    }
+
+    /// Parses zero or more `[key = value, ...]` lists.
+    fn attribute_lists(&mut self) -> Result<Attributes, DotError> {
+        let mut attributes = Attributes::new();
+        while self.peek() == Some(&Token::OpenAttributes) {
+            self.position += 1;
+            while self.peek() != Some(&Token::CloseAttributes) {
+                let (key, _) = self.id()?;
+                self.expect(Token::Equals, "'='")?;
+                let (value, quoted) = self.id()?;
+                attributes.push((key, attribute_value(value, quoted)));
+                if self.peek() == Some(&Token::Separator) {
+                    self.position += 1;
+                }
+            }
+            self.position += 1;
+        }
+        Ok(attributes)
+    }
+
+    /// Returns the node with the given id, creating it with the current
+    /// default attributes if it does not exist yet.
+    fn node(&mut self, id: String) -> &mut NodeData {
+        let position = match self.index.get(&id) {
+            Some(&position) => position,
+            None => {
+                let mut node = NodeData::default();
+                self.node_defaults
+                    .iter()
+                    .cloned()
+                    .for_each(|(key, value)| set_node_attribute(&mut node, key, value));
+                self.index.insert(id.clone(), self.nodes.len());
+                self.nodes.push((id, node));
+                self.nodes.len() - 1
+            }
+        };
+        &mut self.nodes[position].1
+    }
+
+    fn build(self) -> SimpleGraph {
+        let mut graph = if self.directed {
+            SimpleGraph::new()
+        } else {
+            SimpleGraph::new_undirected()
+        };
+        self.nodes.into_iter().for_each(|(id, node)| {
+            graph.add_node(id, node.labels, node.properties);
+        });
+        self.edges
+            .into_iter()
+            .for_each(|(source, target, rel_type, properties)| {
+                graph.add_edge(&source, &target, rel_type, properties);
+            });
+        graph
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or_else(|| self.tokens.last())
+            .map_or(1, |(_, line)| *line)
+    }
+
+    fn keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Id { id, quoted: false }) if id.eq_ignore_ascii_case(keyword))
+    }
+
+    fn id(&mut self) -> Result<(String, bool), DotError> {
+        match self.peek() {
+            Some(Token::Id { id, quoted }) => {
+                let id = (id.clone(), *quoted);
+                self.position += 1;
+                Ok(id)
+            }
+            _ => Err(self.unexpected("an id")),
+        }
+    }
+
+    fn expect(&mut self, token: Token, expected: &str) -> Result<(), DotError> {
+        if self.peek() != Some(&token) {
+            return Err(self.unexpected(expected));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn unexpected(&self, expected: &str) -> DotError {
+        let found = self
+            .peek()
+            .map_or_else(|| "the end of the input".to_string(), Token::to_string);
+        error(
+            self.line(),
+            format!("expected {} but found {}", expected, found),
+        )
+    }
+}
+
+/// Unquoted numerals are numbers, everything else is a string.
+fn attribute_value(value: String, quoted: bool) -> Value {
+    if !quoted && is_numeral(&value) {
+        if let Ok(integer) = value.parse::<i64>() {
+            return Value::Integer(integer);
+        }
+        if let Ok(float) = value.parse::<f64>() {
+            return Value::Float(float);
+        }
+    }
+    Value::String(value)
+}
+
+/// Whether `value` is a DOT numeral, `-?(.[0-9]+|[0-9]+(.[0-9]*)?)`, which,
+/// unlike `f64::from_str`, rejects exponents, `inf` and `nan`.
+fn is_numeral(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    let (integral, fractional) = match digits.split_once('.') {
+        Some((integral, fractional)) => (integral, fractional),
+        None => (digits, ""),
+    };
+    let all_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+    (!integral.is_empty() || !fractional.is_empty())
+        && all_digits(integral)
+        && all_digits(fractional)
+}
+
+fn set_node_attribute(node: &mut NodeData, key: String, value: Value) {
+    if key == "label" {
+        node.labels = vec![value.to_string()];
+    } else {
+        set(&mut node.properties, key, value);
+    }
+}
+
+/// Sets an attribute, replacing an earlier value for the same key.
+fn set(attributes: &mut Attributes, key: String, value: Value) {
+    match attributes.iter_mut().find(|(existing, _)| *existing == key) {
+        Some((_, existing)) => *existing = value,
+        None => attributes.push((key, value)),
+    }
+}
+
+#[cfg(all(feature = "gdl", test))]
+mod tests {
+    use super::*;
+    use crate::{canonicalize, equals, Graph};
+
+    use ::gdl::Graph as GdlGraph;
+
+    fn from_gdl(gdl: &str) -> GdlGraph {
+        gdl.parse::<GdlGraph>().unwrap()
+    }
+
+    #[test]
+    fn test_parse_dot() {
+        let dot = parse_dot(
+            r#"
+            /* a comment */
+            strict digraph people {
+                rankdir = LR;
+                node [shape=box]
+                alice [label="Person", name="Alice", age=42]
+                bob [label=Person]; // implicit attributes follow
+                alice -> bob -> carol [label=KNOWS, weight=0.5]
+                # carol only occurs in an edge
+                alice -> alice
+            }
+            "#,
+        )
+        .unwrap();
+
+        let expected = from_gdl(
+            r#"
+            (alice:Person { shape: "box", name: "Alice", age: 42 }),
+            (bob:Person { shape: "box" }), (carol { shape: "box" }),
+            (alice)-[:KNOWS { weight: 0.5 }]->(bob), (bob)-[:KNOWS { weight: 0.5 }]->(carol),
+            (alice)-->(alice)
+            "#,
+        );
+
+        assert!(dot.is_directed());
+        assert_eq!(canonicalize(&dot), canonicalize(&expected));
+    }
+
+    #[test]
+    fn test_parse_dot_strings() {
+        let dot = parse_dot(r#"digraph { "a b" [quote="say \"hi\"", port="1"] }"#).unwrap();
+
+        let properties = dot
+            .node_properties(&"a b".to_string())
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            properties,
+            vec![
+                ("quote".to_string(), Value::String("say \"hi\"".to_string())),
+                ("port".to_string(), Value::String("1".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_dot_node_defaults() {
+        let dot = parse_dot(
+            "digraph { x; node [color=red]; a [color=blue]; a; x; b -> c; node [color=green] }",
+        )
+        .unwrap();
+
+        let expected = from_gdl(
+            r#"
+            (x), (a { color: "blue" }), (b { color: "red" }), (c { color: "red" }), (b)-->(c)
+            "#,
+        );
+
+        assert_eq!(canonicalize(&dot), canonicalize(&expected));
+    }
+
+    #[test]
+    fn test_parse_dot_numerals() {
+        let dot =
+            parse_dot("digraph { a [kind=nan, size=inf, e=1e5, hex=0x1, x=-.5, y=5., z=-3] }")
+                .unwrap();
+
+        let properties = dot
+            .node_properties(&"a".to_string())
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            properties,
+            vec![
+                ("kind".to_string(), Value::from("nan")),
+                ("size".to_string(), Value::from("inf")),
+                ("e".to_string(), Value::from("1e5")),
+                ("hex".to_string(), Value::from("0x1")),
+                ("x".to_string(), Value::Float(-0.5)),
+                ("y".to_string(), Value::Float(5.0)),
+                ("z".to_string(), Value::Integer(-3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_undirected_dot() {
+        let g1 = parse_dot("graph { a -- b; b -- c }").unwrap();
+        let g2 = parse_dot("graph { b -- a; c -- b }").unwrap();
+        let directed = parse_dot("digraph { a -> b; b -> c }").unwrap();
+
+        assert!(!g1.is_directed());
+        assert!(equals(&g1, &g2));
+        assert!(!equals(&g1, &directed));
+    }
+
+    #[test]
+    fn test_parse_dot_errors() {
+        let message = |dot| parse_dot(dot).unwrap_err().to_string();
+
+        assert_eq!(
+            message("digraph {\n a -- b }"),
+            "invalid DOT in line 2: '--' in a directed graph"
+        );
+        assert_eq!(
+            message("graph { a -> b }"),
+            "invalid DOT in line 1: '->' in an undirected graph"
+        );
+        assert_eq!(
+            message("digraph { a -> }"),
+            "invalid DOT in line 1: expected an id but found '}'"
+        );
+        assert_eq!(
+            message("digraph { subgraph { a } }"),
+            "invalid DOT in line 1: subgraphs are not supported"
+        );
+        assert_eq!(
+            message("tree { }"),
+            "invalid DOT in line 1: expected 'graph' or 'digraph' but found \"tree\""
+        );
+        assert_eq!(
+            message("digraph { a [x=1"),
+            "invalid DOT in line 1: expected an id but found the end of the input"
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use alloc::string::String;
+use core::fmt::{self, Display};
+
+/// A graph that violates the contract of the `Graph` trait.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GraphError {
+    /// A relationship points at a node that is not yielded by `Graph::nodes`.
+    /// The id is rendered with its `Debug` implementation.
+    MissingNode { id: String },
+    /// `Graph::nodes` yields the same node more than once.
+    /// The id is rendered with its `Debug` implementation.
+    DuplicateNode { id: String },
+}
+
+impl Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::MissingNode { id } => write!(f, "Node id {} not found", id),
+            GraphError::DuplicateNode { id } => write!(f, "Node id {} is not unique", id),
+        }
+    }
+}
+
+impl core::error::Error for GraphError {}
+
+/// A comparison exceeded the budget given by `CompareOptions::max_nodes`
+/// or `CompareOptions::max_iterations`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BudgetExceeded {
+    /// A graph has more nodes than allowed.
+    Nodes { limit: usize, actual: usize },
+    /// The search tried more node assignments than allowed.
+    Iterations { limit: usize },
+}
+
+impl Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetExceeded::Nodes { limit, actual } => write!(
+                f,
+                "graph has {} nodes, more than the budget of {}",
+                actual, limit
+            ),
+            BudgetExceeded::Iterations { limit } => {
+                write!(f, "search exceeded the budget of {} iterations", limit)
+            }
+        }
+    }
+}
+
+impl core::error::Error for BudgetExceeded {}
+
+/// Two canonical forms were rendered with different versions of the
+/// canonical format, see `canonical_form_version`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionMismatch {
+    pub left: u32,
+    pub right: u32,
+}
+
+impl Display for VersionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "format version {} differs from format version {}",
+            self.left, self.right
+        )
+    }
+}
+
+impl core::error::Error for VersionMismatch {}
@@ -0,0 +1,578 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display},
+};
+
+use crate::{
+    canonical::Collected,
+    diff::{aligned_rows, AlignedRow},
+    stats::sorted_degrees,
+    structure::UnionFind,
+    Graph,
+};
+
+/// One of two compared graphs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Side::Left => write!(f, "left"),
+            Side::Right => write!(f, "right"),
+        }
+    }
+}
+
+/// A reason why two graphs are not isomorphic.
+///
+/// The `Display` implementation renders a human-readable sentence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Explanation {
+    NodeCount {
+        left: usize,
+        right: usize,
+    },
+    RelationshipCount {
+        left: usize,
+        right: usize,
+    },
+    /// The sorted `(in-degree, out-degree)` pairs of all nodes differ, see
+    /// `degree_sequence`.
+    DegreeSequence {
+        left: Vec<(usize, usize)>,
+        right: Vec<(usize, usize)>,
+    },
+    Label {
+        label: String,
+        only_in: Side,
+    },
+    /// The number of nodes with a label differs.
+    LabelCount {
+        label: String,
+        left: usize,
+        right: usize,
+    },
+    RelationshipType {
+        rel_type: String,
+        only_in: Side,
+    },
+    /// The number of relationships with a type differs.
+    RelationshipTypeCount {
+        rel_type: String,
+        left: usize,
+        right: usize,
+    },
+    /// A property key of a node or relationship.
+    PropertyKey {
+        key: String,
+        only_in: Side,
+    },
+    /// The number of weakly connected components differs.
+    ComponentCount {
+        left: usize,
+        right: usize,
+    },
+    /// A canonical row that has no counterpart in the other graph.
+    Row {
+        row: String,
+        only_in: Side,
+    },
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Explanation::NodeCount { left, right } => {
+                write!(f, "left has {} nodes but right has {}", left, right)
+            }
+            Explanation::RelationshipCount { left, right } => {
+                write!(f, "left has {} relationships but right has {}", left, right)
+            }
+            Explanation::DegreeSequence { left, right } => write!(
+                f,
+                "left has the (in, out) degree sequence {:?} but right has {:?}",
+                left, right
+            ),
+            Explanation::Label { label, only_in } => {
+                write!(f, "label :{} only occurs in {}", label, only_in)
+            }
+            Explanation::LabelCount { label, left, right } => write!(
+                f,
+                "left has {} nodes with label :{} but right has {}",
+                left, label, right
+            ),
+            Explanation::RelationshipType { rel_type, only_in } => {
+                write!(
+                    f,
+                    "relationship type :{} only occurs in {}",
+                    rel_type, only_in
+                )
+            }
+            Explanation::RelationshipTypeCount {
+                rel_type,
+                left,
+                right,
+            } => write!(
+                f,
+                "left has {} relationships of type :{} but right has {}",
+                left, rel_type, right
+            ),
+            Explanation::PropertyKey { key, only_in } => {
+                write!(f, "property key {} only occurs in {}", key, only_in)
+            }
+            Explanation::ComponentCount { left, right } => write!(
+                f,
+                "left has {} weakly connected components but right has {}",
+                left, right
+            ),
+            Explanation::Row { row, only_in } => {
+                write!(f, "row {} only occurs in {}", row, only_in)
+            }
+        }
+    }
+}
+
+/// Explains why two graphs are not isomorphic, ranked by likely cause.
+///
+/// The cheap invariants are checked in this order: node count,
+/// relationship count, degree sequence, labels, relationship types,
+/// property keys and number of weakly connected components. Every invariant
+/// that differs is reported. Only if all of them agree, the canonical rows
+/// that occur in just one of the graphs are reported instead.
+///
+/// Returns an empty list if the graphs are isomorphic.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::explain_inequality;
+///
+/// let g1 = "(a), (b), (a)-->(b)".parse::<GdlGraph>().unwrap();
+/// let g2 = "(a), (b), (c), (a)-->(b)".parse::<GdlGraph>().unwrap();
+///
+/// let explanations = explain_inequality(&g1, &g2);
+/// assert_eq!(explanations[0].to_string(), "left has 2 nodes but right has 3");
+/// ```
+pub fn explain_inequality(left: &impl Graph, right: &impl Graph) -> Vec<Explanation> {
+    let mut explanations = Vec::new();
+    {
+        let left = Collected::new(left);
+        let right = Collected::new(right);
+
+        if left.nodes.len() != right.nodes.len() {
+            explanations.push(Explanation::NodeCount {
+                left: left.nodes.len(),
+                right: right.nodes.len(),
+            });
+        }
+
+        if left.relationships.len() != right.relationships.len() {
+            explanations.push(Explanation::RelationshipCount {
+                left: left.relationships.len(),
+                right: right.relationships.len(),
+            });
+        }
+
+        let (left_degrees, right_degrees) = (sorted_degrees(&left), sorted_degrees(&right));
+        if left_degrees != right_degrees {
+            explanations.push(Explanation::DegreeSequence {
+                left: left_degrees,
+                right: right_degrees,
+            });
+        }
+
+        explanations.extend(
+            only_in(labels(&left), labels(&right))
+                .map(|(label, only_in)| Explanation::Label { label, only_in }),
+        );
+        explanations.extend(
+            only_in(rel_types(&left), rel_types(&right))
+                .map(|(rel_type, only_in)| Explanation::RelationshipType { rel_type, only_in }),
+        );
+        explanations.extend(
+            only_in(property_keys(&left), property_keys(&right))
+                .map(|(key, only_in)| Explanation::PropertyKey { key, only_in }),
+        );
+
+        let (left_components, right_components) = (components(&left), components(&right));
+        if left_components != right_components {
+            explanations.push(Explanation::ComponentCount {
+                left: left_components,
+                right: right_components,
+            });
+        }
+    }
+
+    if explanations.is_empty() {
+        explanations.extend(
+            aligned_rows(left, right)
+                .into_iter()
+                .filter_map(|row| match row {
+                    AlignedRow::Shared(_) => None,
+                    AlignedRow::Removed(row) => Some(Explanation::Row {
+                        row,
+                        only_in: Side::Left,
+                    }),
+                    AlignedRow::Added(row) => Some(Explanation::Row {
+                        row,
+                        only_in: Side::Right,
+                    }),
+                }),
+        );
+    }
+
+    explanations
+}
+
+/// Returns the first of a cascade of cheap invariants that differs between
+/// both graphs: node count, relationship count, number of nodes per label,
+/// number of relationships per type and degree sequence.
+///
+/// Unlike `explain_inequality`, only a single reason is returned. If all
+/// invariants agree but the graphs are still not isomorphic, the first
+/// canonical row that occurs in just one of the graphs is returned. Returns
+/// `None` if the graphs are isomorphic.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::explain_difference;
+///
+/// let g1 = "(a:A), (b:A), (c:B)".parse::<GdlGraph>().unwrap();
+/// let g2 = "(a:A), (b:B), (c:B)".parse::<GdlGraph>().unwrap();
+///
+/// assert_eq!(
+///     explain_difference(&g1, &g2).unwrap().to_string(),
+///     "left has 2 nodes with label :A but right has 1"
+/// );
+/// assert_eq!(explain_difference(&g1, &g1), None);
+/// ```
+pub fn explain_difference<G: Graph, H: Graph>(left: &G, right: &H) -> Option<Explanation> {
+    {
+        let left = Collected::new(left);
+        let right = Collected::new(right);
+
+        if left.nodes.len() != right.nodes.len() {
+            return Some(Explanation::NodeCount {
+                left: left.nodes.len(),
+                right: right.nodes.len(),
+            });
+        }
+
+        if left.relationships.len() != right.relationships.len() {
+            return Some(Explanation::RelationshipCount {
+                left: left.relationships.len(),
+                right: right.relationships.len(),
+            });
+        }
+
+        if let Some((label, left, right)) =
+            first_difference(label_histogram(&left), label_histogram(&right))
+        {
+            return Some(Explanation::LabelCount { label, left, right });
+        }
+
+        if let Some((rel_type, left, right)) =
+            first_difference(rel_type_histogram(&left), rel_type_histogram(&right))
+        {
+            return Some(Explanation::RelationshipTypeCount {
+                rel_type,
+                left,
+                right,
+            });
+        }
+
+        let (left_degrees, right_degrees) = (sorted_degrees(&left), sorted_degrees(&right));
+        if left_degrees != right_degrees {
+            return Some(Explanation::DegreeSequence {
+                left: left_degrees,
+                right: right_degrees,
+            });
+        }
+    }
+
+    aligned_rows(left, right)
+        .into_iter()
+        .find_map(|row| match row {
+            AlignedRow::Shared(_) => None,
+            AlignedRow::Removed(row) => Some(Explanation::Row {
+                row,
+                only_in: Side::Left,
+            }),
+            AlignedRow::Added(row) => Some(Explanation::Row {
+                row,
+                only_in: Side::Right,
+            }),
+        })
+}
+
+/// Counts the nodes with every label, a node with a repeated label counts once.
+fn label_histogram<Id: ?Sized>(graph: &Collected<'_, Id>) -> BTreeMap<String, usize> {
+    let mut histogram = BTreeMap::new();
+    graph.nodes.iter().for_each(|node| {
+        node.labels
+            .iter()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .for_each(|label| *histogram.entry(label.clone()).or_default() += 1);
+    });
+    histogram
+}
+
+fn rel_type_histogram<Id: ?Sized>(graph: &Collected<'_, Id>) -> BTreeMap<String, usize> {
+    let mut histogram = BTreeMap::new();
+    graph
+        .relationships
+        .iter()
+        .for_each(|rel| *histogram.entry(rel.rel_type.clone()).or_default() += 1);
+    histogram
+}
+
+/// Returns the smallest key whose count differs, with its count on both sides.
+fn first_difference(
+    left: BTreeMap<String, usize>,
+    right: BTreeMap<String, usize>,
+) -> Option<(String, usize, usize)> {
+    left.keys()
+        .chain(right.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|key| {
+            let count = |histogram: &BTreeMap<String, usize>| {
+                histogram.get(key).copied().unwrap_or_default()
+            };
+            (key, count(&left), count(&right))
+        })
+        .find(|(_, left, right)| left != right)
+        .map(|(key, left, right)| (key.clone(), left, right))
+}
+
+fn labels<Id: ?Sized>(graph: &Collected<'_, Id>) -> BTreeSet<String> {
+    graph
+        .nodes
+        .iter()
+        .flat_map(|node| node.labels.iter().cloned())
+        .collect()
+}
+
+fn rel_types<Id: ?Sized>(graph: &Collected<'_, Id>) -> BTreeSet<String> {
+    graph
+        .relationships
+        .iter()
+        .map(|rel| rel.rel_type.clone())
+        .collect()
+}
+
+fn property_keys<Id: ?Sized>(graph: &Collected<'_, Id>) -> BTreeSet<String> {
+    graph
+        .nodes
+        .iter()
+        .flat_map(|node| node.properties.iter())
+        .chain(
+            graph
+                .relationships
+                .iter()
+                .flat_map(|rel| rel.properties.iter()),
+        )
+        .map(|(key, _)| key.clone())
+        .collect()
+}
+
+fn components<Id: ?Sized>(graph: &Collected<'_, Id>) -> usize {
+    let mut components = UnionFind::new(graph.nodes.len());
+    graph.relationships.iter().for_each(|rel| {
+        components.union(rel.source, rel.target);
+    });
+    components.count()
+}
+
+/// Yields the sorted elements that occur in only one of both sets.
+fn only_in(
+    left: BTreeSet<String>,
+    right: BTreeSet<String>,
+) -> impl Iterator<Item = (String, Side)> {
+    let left_only = left
+        .difference(&right)
+        .cloned()
+        .map(|element| (element, Side::Left))
+        .collect::<Vec<_>>();
+    let right_only = right
+        .difference(&left)
+        .cloned()
+        .map(|element| (element, Side::Right))
+        .collect::<Vec<_>>();
+    left_only.into_iter().chain(right_only)
+}
+
+#[cfg(all(feature = "gdl", test))]
+mod tests {
+    use super::*;
+    use crate::SimpleGraph;
+
+    use ::gdl::Graph as GdlGraph;
+
+    fn from_gdl(gdl: &str) -> GdlGraph {
+        gdl.parse::<GdlGraph>().unwrap()
+    }
+
+    /// The same undirected star, once with edges a-b and a-c, once with
+    /// edges b-a and c-a.
+    fn undirected_stars() -> (SimpleGraph, SimpleGraph) {
+        let star = |edges: [(&str, &str); 2]| {
+            let mut graph = SimpleGraph::new_undirected();
+            ["a", "b", "c"].iter().for_each(|node| {
+                graph.add_node(*node, Some(node.to_uppercase()), None::<(&str, i64)>);
+            });
+            edges.iter().for_each(|(source, target)| {
+                graph.add_edge(*source, *target, "R", None::<(&str, i64)>);
+            });
+            graph
+        };
+        (
+            star([("a", "b"), ("a", "c")]),
+            star([("b", "a"), ("c", "a")]),
+        )
+    }
+
+    fn explain(left: &str, right: &str) -> Vec<String> {
+        explain_inequality(&from_gdl(left), &from_gdl(right))
+            .into_iter()
+            .map(|explanation| explanation.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_isomorphic_graphs_have_no_explanation() {
+        assert!(explain("(a:A)-->(b)", "(x)<--(y:A)").is_empty());
+    }
+
+    #[test]
+    fn test_undirected_graphs_with_flipped_edges_have_no_explanation() {
+        let (left, right) = undirected_stars();
+
+        assert!(crate::equals(&left, &right));
+        assert!(explain_inequality(&left, &right).is_empty());
+    }
+
+    #[test]
+    fn test_counts_are_ranked_first() {
+        assert_eq!(
+            explain("(a)-->(b), (c)", "(a)-->(b), (c), (d), (a)-->(c)"),
+            vec![
+                "left has 3 nodes but right has 4",
+                "left has 1 relationships but right has 2",
+                "left has the (in, out) degree sequence [(0, 0), (0, 1), (1, 0)] \
+                 but right has [(0, 0), (0, 2), (1, 0), (1, 0)]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schema() {
+        assert_eq!(
+            explain("(a:A {x: 1})-[:R]->(b)", "(a:B {y: 1})-[:S]->(b)"),
+            vec![
+                "label :A only occurs in left",
+                "label :B only occurs in right",
+                "relationship type :R only occurs in left",
+                "relationship type :S only occurs in right",
+                "property key x only occurs in left",
+                "property key y only occurs in right",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_components() {
+        assert_eq!(
+            explain(
+                "(a)-->(b)-->(c)-->(a), (d)-->(e)-->(f)-->(d)",
+                "(a)-->(b)-->(c)-->(d)-->(e)-->(f)-->(a)"
+            ),
+            vec!["left has 2 weakly connected components but right has 1"]
+        );
+    }
+
+    #[test]
+    fn test_falls_back_to_rows() {
+        assert_eq!(
+            explain("(a {v: 1}), (b {v: 2})", "(a {v: 1}), (b {v: 3})"),
+            vec![
+                "row ( { v: 2 }) => out:  in:  only occurs in left",
+                "row ( { v: 3 }) => out:  in:  only occurs in right",
+            ]
+        );
+    }
+
+    fn difference(left: &str, right: &str) -> Option<String> {
+        explain_difference(&from_gdl(left), &from_gdl(right))
+            .map(|explanation| explanation.to_string())
+    }
+
+    #[test]
+    fn test_difference_of_isomorphic_graphs() {
+        assert_eq!(difference("(a:A)-->(b)", "(x)<--(y:A)"), None);
+    }
+
+    #[test]
+    fn test_difference_of_undirected_graphs_with_flipped_edges() {
+        let (left, right) = undirected_stars();
+
+        assert!(crate::equals(&left, &right));
+        assert_eq!(explain_difference(&left, &right), None);
+    }
+
+    #[test]
+    fn test_difference_in_counts() {
+        assert_eq!(
+            difference("(a)-->(b), (c)", "(a)-->(b), (c), (d)"),
+            Some("left has 3 nodes but right has 4".to_string())
+        );
+        assert_eq!(
+            difference("(a)-->(b), (c)", "(a)-->(b), (c), (a)-->(c)"),
+            Some("left has 1 relationships but right has 2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_difference_in_label_histogram() {
+        assert_eq!(
+            difference("(a:A:B), (b:A), (c:A)", "(a:A:B), (b:A), (c:B)"),
+            Some("left has 3 nodes with label :A but right has 2".to_string())
+        );
+        assert_eq!(
+            difference("(a:A), (b)", "(a:A), (b:C)"),
+            Some("left has 0 nodes with label :C but right has 1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_difference_in_rel_type_histogram() {
+        assert_eq!(
+            difference("(a)-[:R]->(b)-[:R]->(c)", "(a)-[:R]->(b)-[:S]->(c)"),
+            Some("left has 2 relationships of type :R but right has 1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_difference_in_degree_sequence() {
+        assert_eq!(
+            difference("(a)-->(b), (a)-->(c)", "(a)-->(b), (c)-->(b)"),
+            Some(
+                "left has the (in, out) degree sequence [(0, 2), (1, 0), (1, 0)] \
+                 but right has [(0, 1), (0, 1), (2, 0)]"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_difference_falls_back_to_rows() {
+        assert_eq!(
+            difference("(a {v: 1}), (b {v: 2})", "(a {v: 1}), (b {v: 3})"),
+            Some("row ( { v: 2 }) => out:  in:  only occurs in left".to_string())
+        );
+    }
+}
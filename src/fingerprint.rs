@@ -0,0 +1,200 @@
+use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::{canonical, CompareOptions, Graph};
+
+/// Hashes the canonical row of every node.
+///
+/// A row contains the node's labels and properties as well as its
+/// relationships and the labels and properties of its neighbors. Changing
+/// a node therefore also changes the fingerprints of its neighbors.
+///
+/// The hash is FNV-1a, which is stable across platforms and releases.
+pub fn node_fingerprints<G: Graph>(graph: &G) -> HashMap<&G::NodeId, u64> {
+    let graph = canonical::collect(graph, &CompareOptions::default());
+    graph
+        .rows()
+        .into_iter()
+        .zip(&graph.nodes)
+        .map(|(row, node)| (node.id, fnv1a(row.as_bytes())))
+        .collect()
+}
+
+/// Groups nodes with identical canonical rows.
+///
+/// Nodes that are interchangeable by an automorphism always share an orbit.
+/// The converse does not hold in general, since a row only describes the
+/// immediate neighborhood of a node. Orbits are sorted by their row, nodes
+/// within an orbit by their position in `graph.nodes()`.
+pub fn node_orbits<G: Graph>(graph: &G) -> Vec<Vec<&G::NodeId>> {
+    let graph = canonical::collect(graph, &CompareOptions::default());
+    let mut orbits = BTreeMap::<String, Vec<&G::NodeId>>::new();
+    graph
+        .rows()
+        .into_iter()
+        .zip(&graph.nodes)
+        .for_each(|(row, node)| orbits.entry(row).or_default().push(node.id));
+    orbits.into_values().collect()
+}
+
+/// A snapshot of node fingerprints that reports which nodes changed.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::FingerprintIndex;
+///
+/// let before = "(a {v: 1}), (b {v: 2}), (c {v: 3}), (a)-->(b)".parse::<GdlGraph>().unwrap();
+/// let after = "(a {v: 1}), (b {v: 2}), (c {v: 4}), (a)-->(b)".parse::<GdlGraph>().unwrap();
+///
+/// let mut index = FingerprintIndex::new(&before);
+/// let changed = index.update(&after);
+///
+/// assert_eq!(changed.into_iter().collect::<Vec<_>>(), vec!["c".to_string()]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct FingerprintIndex<K> {
+    fingerprints: HashMap<K, u64>,
+}
+
+impl<K: Hash + Eq + Clone> FingerprintIndex<K> {
+    pub fn new<G>(graph: &G) -> Self
+    where
+        G: Graph,
+        G::NodeId: ToOwned<Owned = K>,
+    {
+        Self {
+            fingerprints: owned_fingerprints(graph),
+        }
+    }
+
+    /// Returns the ids of all nodes that were added, removed or whose
+    /// fingerprint differs between the snapshot and `graph`.
+    pub fn changed<G>(&self, graph: &G) -> HashSet<K>
+    where
+        G: Graph,
+        G::NodeId: ToOwned<Owned = K>,
+    {
+        changed(&self.fingerprints, &owned_fingerprints(graph))
+    }
+
+    /// Like `changed`, but also replaces the snapshot with `graph`.
+    pub fn update<G>(&mut self, graph: &G) -> HashSet<K>
+    where
+        G: Graph,
+        G::NodeId: ToOwned<Owned = K>,
+    {
+        let fingerprints = owned_fingerprints(graph);
+        let changed = changed(&self.fingerprints, &fingerprints);
+        self.fingerprints = fingerprints;
+        changed
+    }
+
+    /// Returns the fingerprint of a node in the snapshot. Like
+    /// `HashMap::get`, the id may be any borrowed form of `K`, e.g., `&str`
+    /// for `String` ids.
+    pub fn fingerprint<Q>(&self, node_id: &Q) -> Option<u64>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.fingerprints.get(node_id).copied()
+    }
+}
+
+fn owned_fingerprints<G>(graph: &G) -> HashMap<<G::NodeId as ToOwned>::Owned, u64>
+where
+    G: Graph,
+    G::NodeId: ToOwned,
+    <G::NodeId as ToOwned>::Owned: Hash + Eq,
+{
+    node_fingerprints(graph)
+        .into_iter()
+        .map(|(node, fingerprint)| (node.to_owned(), fingerprint))
+        .collect()
+}
+
+fn changed<K: Hash + Eq + Clone>(before: &HashMap<K, u64>, after: &HashMap<K, u64>) -> HashSet<K> {
+    before
+        .iter()
+        .filter(|(node, fingerprint)| after.get(node) != Some(fingerprint))
+        .map(|(node, _)| node)
+        .chain(after.keys().filter(|node| !before.contains_key(node)))
+        .cloned()
+        .collect()
+}
+
+/// 64-bit FNV-1a.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(all(feature = "gdl", test))]
+mod tests {
+    use super::*;
+
+    use ::gdl::Graph as GdlGraph;
+
+    fn from_gdl(gdl: &str) -> GdlGraph {
+        gdl.parse::<GdlGraph>().unwrap()
+    }
+
+    fn sorted(changed: HashSet<String>) -> Vec<String> {
+        let mut changed = changed.into_iter().collect::<Vec<_>>();
+        changed.sort();
+        changed
+    }
+
+    #[test]
+    fn test_only_mutated_node_changes() {
+        let before = from_gdl("(a {v: 1}), (b {v: 2}), (c {v: 3}), (a)-->(b)");
+        let after = from_gdl("(a {v: 1}), (b {v: 2}), (c {v: 4}), (a)-->(b)");
+
+        let mut index = FingerprintIndex::new(&before);
+        assert!(index.changed(&before).is_empty());
+        assert_eq!(
+            index.fingerprint("c"),
+            node_fingerprints(&before).get("c").copied()
+        );
+        assert_eq!(index.fingerprint("d"), None);
+        assert_eq!(sorted(index.update(&after)), vec!["c"]);
+        assert!(index.update(&after).is_empty());
+    }
+
+    #[test]
+    fn test_neighbors_of_mutated_node_change() {
+        let before = from_gdl("(a {v: 1}), (b {v: 2}), (c {v: 3}), (a)-->(b)");
+        let after = from_gdl("(a {v: 5}), (b {v: 2}), (c {v: 3}), (a)-->(b)");
+
+        let index = FingerprintIndex::new(&before);
+        assert_eq!(sorted(index.changed(&after)), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_added_and_removed_nodes_change() {
+        let before = from_gdl("(a), (b)");
+        let after = from_gdl("(a), (c)");
+
+        let index = FingerprintIndex::new(&before);
+        assert_eq!(sorted(index.changed(&after)), vec!["b", "c"]);
+    }
+
+    #[test]
+    fn test_node_orbits() {
+        let g = from_gdl("(a)-->(b)<--(c), (d)");
+        let mut orbits = node_orbits(&g);
+        orbits.iter_mut().for_each(|orbit| orbit.sort());
+
+        assert_eq!(orbits, vec![vec!["d"], vec!["b"], vec!["a", "c"]]);
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
@@ -0,0 +1,282 @@
+use std::{cell::OnceCell, collections::HashMap, fmt::Debug, hash::Hash};
+
+use crate::{
+    graph::{Graph, LabelIterator, NodesIterator, PropertyIterator, RelationshipIterator},
+    Value,
+};
+
+type PropertyList = Vec<(String, Value)>;
+type Labels<'f, N> = Box<dyn Fn(&N) -> Vec<String> + 'f>;
+type Properties<'f, N> = Box<dyn Fn(&N) -> PropertyList + 'f>;
+type OutEdges<'f, N> = Box<dyn Fn(&N) -> Vec<(N, String)> + 'f>;
+type RelationshipProperties<'f, N> = Box<dyn Fn(&N, &N, &str) -> PropertyList + 'f>;
+
+/// A graph defined by closures instead of stored data.
+///
+/// The node ids are produced once, when the graph is created. Labels,
+/// properties and relationships of a node are computed on first access and
+/// cached afterwards, so procedurally defined graphs can be compared without
+/// building them up front.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::{equals, FnGraph};
+///
+/// let grid = FnGraph::new(|| (0..2).flat_map(|x| (0..2).map(move |y| (x, y))))
+///     .labels(|_| ["Cell"])
+///     .out_edges(|&(x, y)| {
+///         let mut edges = Vec::new();
+///         if x + 1 < 2 {
+///             edges.push(((x + 1, y), "RIGHT"));
+///         }
+///         if y + 1 < 2 {
+///             edges.push(((x, y + 1), "DOWN"));
+///         }
+///         edges
+///     });
+///
+/// let expected = "
+///     (a:Cell), (b:Cell), (c:Cell), (d:Cell),
+///     (a)-[:RIGHT]->(b), (a)-[:DOWN]->(c), (b)-[:DOWN]->(d), (c)-[:RIGHT]->(d)
+/// "
+/// .parse::<GdlGraph>()
+/// .unwrap();
+///
+/// assert!(equals(&grid, &expected));
+/// ```
+pub struct FnGraph<'f, N> {
+    nodes: Vec<N>,
+    index: HashMap<N, usize>,
+    labels: Labels<'f, N>,
+    properties: Properties<'f, N>,
+    out_edges: OutEdges<'f, N>,
+    relationship_properties: RelationshipProperties<'f, N>,
+    cache: Vec<OnceCell<NodeData>>,
+    incoming: OnceCell<Vec<Vec<(usize, usize)>>>,
+}
+
+struct NodeData {
+    labels: Vec<String>,
+    properties: PropertyList,
+    outgoing: Vec<(usize, String, PropertyList)>,
+}
+
+impl<'f, N: Debug + Hash + Eq + Clone> FnGraph<'f, N> {
+    /// Creates a graph over the node ids produced by `nodes`.
+    ///
+    /// Nodes have no labels, properties or relationships until the
+    /// corresponding closures are set.
+    pub fn new<I>(nodes: impl FnOnce() -> I) -> Self
+    where
+        I: IntoIterator<Item = N>,
+    {
+        let mut index = HashMap::new();
+        let nodes = nodes()
+            .into_iter()
+            .filter(|node| {
+                let next = index.len();
+                index.insert(node.clone(), next).is_none()
+            })
+            .collect::<Vec<_>>();
+        let cache = nodes.iter().map(|_| OnceCell::new()).collect();
+
+        Self {
+            nodes,
+            index,
+            labels: Box::new(|_| Vec::new()),
+            properties: Box::new(|_| Vec::new()),
+            out_edges: Box::new(|_| Vec::new()),
+            relationship_properties: Box::new(|_, _, _| Vec::new()),
+            cache,
+            incoming: OnceCell::new(),
+        }
+    }
+
+    pub fn labels<L>(mut self, labels: impl Fn(&N) -> L + 'f) -> Self
+    where
+        L: IntoIterator,
+        L::Item: Into<String>,
+    {
+        self.labels = Box::new(move |node| labels(node).into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn properties<P, K, V>(mut self, properties: impl Fn(&N) -> P + 'f) -> Self
+    where
+        P: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<Value>,
+    {
+        self.properties = Box::new(move |node| into_properties(properties(node)));
+        self
+    }
+
+    /// Sets the closure that yields the target and type of every outgoing relationship.
+    ///
+    /// Targets must be among the node ids of the graph.
+    pub fn out_edges<E, T>(mut self, out_edges: impl Fn(&N) -> E + 'f) -> Self
+    where
+        E: IntoIterator<Item = (N, T)>,
+        T: Into<String>,
+    {
+        self.out_edges = Box::new(move |node| {
+            out_edges(node)
+                .into_iter()
+                .map(|(target, rel_type)| (target, rel_type.into()))
+                .collect()
+        });
+        self
+    }
+
+    /// Sets the closure that yields the properties of a relationship,
+    /// given its source, target and type.
+    pub fn relationship_properties<P, K, V>(
+        mut self,
+        properties: impl Fn(&N, &N, &str) -> P + 'f,
+    ) -> Self
+    where
+        P: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<Value>,
+    {
+        self.relationship_properties = Box::new(move |source, target, rel_type| {
+            into_properties(properties(source, target, rel_type))
+        });
+        self
+    }
+
+    fn node_index(&self, node_id: &N) -> usize {
+        *self
+            .index
+            .get(node_id)
+            .unwrap_or_else(|| panic!("Node id {:?} not found", node_id))
+    }
+
+    fn data(&self, node: usize) -> &NodeData {
+        self.cache[node].get_or_init(|| {
+            let id = &self.nodes[node];
+            let outgoing = (self.out_edges)(id)
+                .into_iter()
+                .map(|(target, rel_type)| {
+                    let properties = (self.relationship_properties)(id, &target, &rel_type);
+                    (self.node_index(&target), rel_type, properties)
+                })
+                .collect();
+
+            NodeData {
+                labels: (self.labels)(id),
+                properties: (self.properties)(id),
+                outgoing,
+            }
+        })
+    }
+
+    /// Returns, per node, the source node and position of its incoming relationships.
+    fn incoming(&self) -> &[Vec<(usize, usize)>] {
+        self.incoming.get_or_init(|| {
+            let mut incoming = vec![Vec::new(); self.nodes.len()];
+            (0..self.nodes.len()).for_each(|source| {
+                self.data(source).outgoing.iter().enumerate().for_each(
+                    |(position, (target, _, _))| incoming[*target].push((source, position)),
+                )
+            });
+            incoming
+        })
+    }
+}
+
+fn into_properties<P, K, V>(properties: P) -> PropertyList
+where
+    P: IntoIterator<Item = (K, V)>,
+    K: Into<String>,
+    V: Into<Value>,
+{
+    properties
+        .into_iter()
+        .map(|(key, value)| (key.into(), value.into()))
+        .collect()
+}
+
+impl<'f, N: Debug + Hash + Eq + Clone> Graph for FnGraph<'f, N> {
+    type NodeId = N;
+
+    type NodeLabel = str;
+
+    type RelationshipType = str;
+
+    type PropertyKey = str;
+
+    type PropertyValue = Value;
+
+    fn nodes(&self) -> NodesIterator<'_, &Self::NodeId> {
+        Box::new(self.nodes.iter())
+    }
+
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn node_labels(&self, node_id: &Self::NodeId) -> LabelIterator<'_, &Self::NodeLabel> {
+        let data = self.data(self.node_index(node_id));
+        Box::new(data.labels.iter().map(String::as_str))
+    }
+
+    fn node_properties(
+        &self,
+        node_id: &Self::NodeId,
+    ) -> PropertyIterator<'_, &Self::PropertyKey, &Self::PropertyValue> {
+        let data = self.data(self.node_index(node_id));
+        Box::new(
+            data.properties
+                .iter()
+                .map(|(key, value)| (key.as_str(), value)),
+        )
+    }
+
+    fn outgoing_relationships<'a, 'b: 'a>(
+        &'a self,
+        node_id: &'b Self::NodeId,
+    ) -> RelationshipIterator<
+        'a,
+        &'a Self::NodeId,
+        &'a Self::RelationshipType,
+        &'a Self::PropertyKey,
+        &'a Self::PropertyValue,
+    > {
+        let data = self.data(self.node_index(node_id));
+        Box::new(
+            data.outgoing
+                .iter()
+                .map(move |(target, rel_type, properties)| {
+                    let key = (&self.nodes[*target], rel_type.as_str());
+                    let value: PropertyIterator<&str, &Value> =
+                        Box::new(properties.iter().map(|(key, value)| (key.as_str(), value)));
+                    (key, value)
+                }),
+        )
+    }
+
+    fn incoming_relationships<'a, 'b: 'a>(
+        &'a self,
+        node_id: &'b Self::NodeId,
+    ) -> RelationshipIterator<
+        'a,
+        &'a Self::NodeId,
+        &'a Self::RelationshipType,
+        &'a Self::PropertyKey,
+        &'a Self::PropertyValue,
+    > {
+        let incoming = &self.incoming()[self.node_index(node_id)];
+        Box::new(incoming.iter().map(move |&(source, position)| {
+            let (_, rel_type, properties) = &self.data(source).outgoing[position];
+            let key = (&self.nodes[source], rel_type.as_str());
+            let value: PropertyIterator<&str, &Value> =
+                Box::new(properties.iter().map(|(key, value)| (key.as_str(), value)));
+            (key, value)
+        }))
+    }
+
+    fn property_value(&self, value: &Self::PropertyValue) -> Value {
+        value.clone()
+    }
+}
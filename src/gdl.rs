@@ -1,7 +1,18 @@
 use gdl::CypherValue;
 
-use crate::graph::{Graph, LabelIterator, NodesIterator, PropertyIterator};
+#[doc(hidden)]
+pub use gdl::Graph as GdlGraph;
 
+use crate::{
+    graph::{
+        AllRelationshipsIterator, Graph, LabelIterator, NodesIterator, PropertyIterator,
+        RelationshipIterator,
+    },
+    Value,
+};
+
+/// Untyped relationships get the empty type. This does not alias typed
+/// relationships, since gdl does not accept an empty relationship type.
 impl Graph for gdl::Graph {
     type NodeId = str;
 
@@ -13,11 +24,19 @@ impl Graph for gdl::Graph {
 
     type PropertyValue = CypherValue;
 
-    fn nodes(&self) -> NodesIterator<&Self::NodeId> {
+    fn nodes(&self) -> NodesIterator<'_, &Self::NodeId> {
         Box::new(self.nodes().map(|node| node.variable()))
     }
 
-    fn node_labels(&self, node_id: &Self::NodeId) -> LabelIterator<&Self::NodeLabel> {
+    fn node_count(&self) -> usize {
+        gdl::Graph::node_count(self)
+    }
+
+    fn relationship_count(&self) -> usize {
+        gdl::Graph::relationship_count(self)
+    }
+
+    fn node_labels(&self, node_id: &Self::NodeId) -> LabelIterator<'_, &Self::NodeLabel> {
         let node = self
             .get_node(node_id)
             .unwrap_or_else(|| panic!("Node id {} not found", node_id));
@@ -27,7 +46,7 @@ impl Graph for gdl::Graph {
     fn node_properties(
         &self,
         node_id: &Self::NodeId,
-    ) -> PropertyIterator<&Self::PropertyKey, &Self::PropertyValue> {
+    ) -> PropertyIterator<'_, &Self::PropertyKey, &Self::PropertyValue> {
         let node = self
             .get_node(node_id)
             .unwrap_or_else(|| panic!("Node id {} not found", node_id));
@@ -37,36 +56,303 @@ impl Graph for gdl::Graph {
     fn outgoing_relationships<'a, 'b: 'a>(
         &'a self,
         node_id: &'b Self::NodeId,
-    ) -> PropertyIterator<
+    ) -> RelationshipIterator<
         'a,
-        (&'a Self::NodeId, &'a Self::RelationshipType),
-        PropertyIterator<'a, &'a Self::PropertyKey, &'a Self::PropertyValue>,
+        &'a Self::NodeId,
+        &'a Self::RelationshipType,
+        &'a Self::PropertyKey,
+        &'a Self::PropertyValue,
     > {
-        Box::new(self.relationships().filter_map(move |rel| {
-            (rel.source() == node_id).then(|| {
-                let key = (rel.target(), rel.rel_type().unwrap_or(""));
-                let value: Box<dyn Iterator<Item = (&str, &CypherValue)>> =
-                    Box::new(rel.properties());
-                (key, value)
-            })
-        }))
+        Box::new(
+            self.relationships()
+                .filter(move |rel| rel.source() == node_id)
+                .map(|rel| {
+                    let key = (rel.target(), rel.rel_type().unwrap_or(""));
+                    let value: PropertyIterator<&str, &CypherValue> = Box::new(rel.properties());
+                    (key, value)
+                }),
+        )
     }
 
     fn incoming_relationships<'a, 'b: 'a>(
         &'a self,
         node_id: &'b Self::NodeId,
-    ) -> PropertyIterator<
+    ) -> RelationshipIterator<
         'a,
-        (&'a Self::NodeId, &'a Self::RelationshipType),
-        PropertyIterator<'a, &'a Self::PropertyKey, &'a Self::PropertyValue>,
+        &'a Self::NodeId,
+        &'a Self::RelationshipType,
+        &'a Self::PropertyKey,
+        &'a Self::PropertyValue,
     > {
-        Box::new(self.relationships().filter_map(move |rel| {
-            (rel.target() == node_id).then(|| {
-                let key = (rel.source(), rel.rel_type().unwrap_or(""));
-                let value: Box<dyn Iterator<Item = (&str, &CypherValue)>> =
-                    Box::new(rel.properties());
-                (key, value)
-            })
+        Box::new(
+            self.relationships()
+                .filter(move |rel| rel.target() == node_id)
+                .map(|rel| {
+                    let key = (rel.source(), rel.rel_type().unwrap_or(""));
+                    let value: PropertyIterator<&str, &CypherValue> = Box::new(rel.properties());
+                    (key, value)
+                }),
+        )
+    }
+
+    /// Reads all relationships in a single pass, since looking up the
+    /// relationships of a node scans all relationships of the graph.
+    fn relationships(
+        &self,
+    ) -> AllRelationshipsIterator<
+        '_,
+        &Self::NodeId,
+        &Self::RelationshipType,
+        &Self::PropertyKey,
+        &Self::PropertyValue,
+    > {
+        Box::new(gdl::Graph::relationships(self).map(|rel| {
+            let properties: PropertyIterator<&str, &CypherValue> = Box::new(rel.properties());
+            (
+                rel.source(),
+                (rel.target(), rel.rel_type().unwrap_or("")),
+                properties,
+            )
         }))
     }
+
+    fn property_value(&self, value: &Self::PropertyValue) -> Value {
+        match value {
+            CypherValue::Float(float) => Value::Float(*float),
+            CypherValue::Integer(integer) => Value::Integer(*integer),
+            CypherValue::String(string) => Value::String(string.clone()),
+            CypherValue::Boolean(boolean) => Value::Boolean(*boolean),
+            CypherValue::List(list) => {
+                let debug = format!("{:?}", list);
+                let value = DebugParser::parse(&debug);
+                debug_assert!(
+                    value.is_some(),
+                    "unexpected Debug output of a gdl list: {}",
+                    debug
+                );
+                value.unwrap_or_else(|| Value::String(list.to_string()))
+            }
+        }
+    }
+}
+
+/// Reads a gdl list from its `Debug` output, e.g. `List([Integer(1), String("a")])`,
+/// since gdl does not expose the elements of a list otherwise.
+///
+/// The `Debug` output is not a stable API of gdl. This parser was verified
+/// against gdl 0.2.7. If another version changes the output, parsing fails
+/// and the list falls back to a string, which breaks options that work on
+/// list elements, such as `sort_lists`. Debug builds, and therefore the
+/// tests, assert that parsing succeeds so such a change does not go unnoticed.
+struct DebugParser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> DebugParser<'a> {
+    fn parse(debug: &'a str) -> Option<Value> {
+        let mut parser = Self { rest: debug };
+        let value = parser.value()?;
+        parser.rest.is_empty().then_some(value)
+    }
+
+    fn value(&mut self) -> Option<Value> {
+        let open = self.rest.find('(')?;
+        let tag = &self.rest[..open];
+        self.rest = &self.rest[open + 1..];
+
+        let value = match tag {
+            "Integer" => Value::Integer(self.scalar()?.parse().ok()?),
+            "Float" => Value::Float(self.scalar()?.parse().ok()?),
+            "Boolean" => Value::Boolean(self.scalar()?.parse().ok()?),
+            "String" => Value::String(self.string()?),
+            // `CypherValue::List` wraps the `List` struct, which wraps the elements.
+            "List" if self.rest.starts_with("List(") => self.value()?,
+            "List" => Value::List(self.elements()?),
+            _ => return None,
+        };
+
+        self.expect(")")?;
+        Some(value)
+    }
+
+    fn scalar(&mut self) -> Option<&'a str> {
+        let end = self.rest.find(')')?;
+        let scalar = &self.rest[..end];
+        self.rest = &self.rest[end..];
+        Some(scalar)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.expect("\"")?;
+        let mut string = String::new();
+        let mut chars = self.rest.char_indices();
+
+        while let Some((position, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.rest = &self.rest[position + 1..];
+                    return Some(string);
+                }
+                '\\' => string.push(match chars.next()?.1 {
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    '0' => '\0',
+                    'u' => {
+                        let hex = chars
+                            .by_ref()
+                            .map(|(_, c)| c)
+                            .skip_while(|c| *c == '{')
+                            .take_while(|c| *c != '}')
+                            .collect::<String>();
+                        char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?
+                    }
+                    escaped => escaped,
+                }),
+                c => string.push(c),
+            }
+        }
+
+        None
+    }
+
+    fn elements(&mut self) -> Option<Vec<Value>> {
+        self.expect("[")?;
+        let mut elements = Vec::new();
+        while !self.rest.starts_with(']') {
+            if !elements.is_empty() {
+                self.expect(", ")?;
+            }
+            elements.push(self.value()?);
+        }
+        self.expect("]")?;
+        Some(elements)
+    }
+
+    fn expect(&mut self, token: &str) -> Option<()> {
+        self.rest = self.rest.strip_prefix(token)?;
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleGraph;
+
+    fn property_value(gdl: &str) -> Value {
+        let graph = gdl.parse::<gdl::Graph>().unwrap();
+        let node = graph.get_node("a").unwrap();
+        let (_, value) = node.properties().next().unwrap();
+        Graph::property_value(&graph, value)
+    }
+
+    #[test]
+    fn test_list_property_value() {
+        assert_eq!(
+            property_value(r#"(a {v: [1, 2.5, "x \"y\"", true, 'z']})"#),
+            Value::List(vec![
+                Value::Integer(1),
+                Value::Float(2.5),
+                // gdl keeps the escape characters of a string literal
+                Value::String(r#"x \"y\""#.to_string()),
+                Value::Boolean(true),
+                Value::String("z".to_string()),
+            ])
+        );
+        assert_eq!(property_value("(a {v: []})"), Value::List(vec![]));
+    }
+
+    #[test]
+    fn test_large_and_decimal_property_values() {
+        let equal = |left: &str, right: &str| {
+            crate::equals(
+                &left.parse::<gdl::Graph>().unwrap(),
+                &right.parse::<gdl::Graph>().unwrap(),
+            )
+        };
+
+        // gdl parses integers into `i64` and panics on larger literals, so
+        // the extremes it accepts have to render exactly.
+        assert!(equal(
+            "(a {v: 9223372036854775807})",
+            "(b {v: 9223372036854775807})"
+        ));
+        assert!(!equal(
+            "(a {v: 9223372036854775807})",
+            "(b {v: 9223372036854775806})"
+        ));
+        assert!(!equal(
+            "(a {v: -9223372036854775807})",
+            "(b {v: 9223372036854775807})"
+        ));
+        // gdl has no decimal type, floats render as the shortest string that
+        // reads back as the same `f64`, without trailing zeros.
+        assert!(equal("(a {v: 1.50})", "(b {v: 1.5})"));
+        assert!(equal("(a {v: 2.000, w: [0.10]})", "(b {v: 2.0, w: [0.1]})"));
+        assert!(equal(
+            "(a {v: 100000000000000000000.0})",
+            "(b {v: 100000000000000000000.00})"
+        ));
+        assert!(!equal("(a {v: 0.30000000000000004})", "(b {v: 0.3})"));
+        assert_eq!(
+            property_value("(a {v: 12345678901234567.0})"),
+            Value::Float(12345678901234567.0)
+        );
+    }
+
+    #[test]
+    fn test_relationships() {
+        let graph = "(a)-[:R {w: 1}]->(b), (b)-->(a), (a)-[:R]->(a)"
+            .parse::<gdl::Graph>()
+            .unwrap();
+
+        let mut relationships = Graph::relationships(&graph)
+            .map(|(source, (target, rel_type), properties)| {
+                let properties = properties
+                    .map(|(key, value)| format!("{}: {}", key, value))
+                    .collect::<Vec<_>>();
+                (source, target, rel_type, properties)
+            })
+            .collect::<Vec<_>>();
+        relationships.sort();
+
+        assert_eq!(
+            relationships,
+            vec![
+                ("a", "a", "R", vec![]),
+                ("a", "b", "R", vec!["w: 1".to_string()]),
+                ("b", "a", "", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relationship_endpoints_are_nodes() {
+        // `b` and `c` are only referenced by relationships, gdl declares them
+        // implicitly, so every endpoint is part of `nodes`.
+        let graph = "(a:A), (a)-->(b), (c)-->(a)".parse::<gdl::Graph>().unwrap();
+
+        let mut nodes = Graph::nodes(&graph).collect::<Vec<_>>();
+        nodes.sort_unstable();
+        assert_eq!(nodes, vec!["a", "b", "c"]);
+        assert!(crate::try_canonicalize_with(&graph, &Default::default()).is_ok());
+    }
+
+    #[test]
+    fn test_untyped_relationships() {
+        let typed = "(a)-[:REL]->(b)".parse::<gdl::Graph>().unwrap();
+        let untyped = "(a)-->(b)".parse::<gdl::Graph>().unwrap();
+        let untyped_with_brackets = "(a)-[]->(b)".parse::<gdl::Graph>().unwrap();
+
+        assert!(!crate::equals(&typed, &untyped));
+        assert!(crate::equals(&untyped, &untyped_with_brackets));
+        assert!("(a)-[:]->(b)".parse::<gdl::Graph>().is_err());
+
+        let mut simple = SimpleGraph::new();
+        simple.add_node("a", None::<&str>, None::<(&str, i64)>);
+        simple.add_node("b", None::<&str>, None::<(&str, i64)>);
+        simple.add_edge("a", "b", "", None::<(&str, i64)>);
+        assert!(crate::equals(&untyped, &simple));
+    }
 }
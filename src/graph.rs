@@ -1,45 +1,166 @@
-use std::{
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+};
+use core::{
     fmt::{Debug, Display},
     hash::Hash,
 };
 
+use crate::{CanonicalForm, Value};
+
 pub type NodesIterator<'a, T> = Box<dyn Iterator<Item = T> + 'a>;
 pub type LabelIterator<'a, T> = Box<dyn Iterator<Item = T> + 'a>;
 pub type PropertyIterator<'a, K, V> = Box<dyn Iterator<Item = (K, V)> + 'a>;
+pub type RelationshipIterator<'a, N, T, K, V> =
+    PropertyIterator<'a, (N, T), PropertyIterator<'a, K, V>>;
+pub type AllRelationshipsIterator<'a, N, T, K, V> =
+    Box<dyn Iterator<Item = (N, (N, T), PropertyIterator<'a, K, V>)> + 'a>;
 
+/// A read-only view of a property graph.
+///
+/// Graphs are compared by their rendered labels, relationship types and
+/// properties, so two implementations of this trait can be compared with
+/// each other, e.g., a `gdl::Graph` with a `SimpleGraph`. Labels, types and
+/// keys are rendered through `Display`, property values through the `Value`
+/// returned by `property_value`. Such comparisons rely on both
+/// implementations rendering the same logical value the same way, so an
+/// adapter whose values display differently, e.g., with quoted strings,
+/// should override `property_value`.
 pub trait Graph {
     type NodeId: Debug + Hash + Eq + ?Sized;
 
     type NodeLabel: Display + ?Sized;
 
+    /// The type of a relationship. Untyped relationships are represented by
+    /// a type that renders as the empty string, so they cannot be told apart
+    /// from relationships whose type is the empty string.
     type RelationshipType: Display + ?Sized;
 
     type PropertyKey: Display + ?Sized;
 
     type PropertyValue: Display + ?Sized;
 
-    fn nodes(&self) -> NodesIterator<&Self::NodeId>;
+    fn nodes(&self) -> NodesIterator<'_, &Self::NodeId>;
 
-    fn node_labels(&self, node_id: &Self::NodeId) -> LabelIterator<&Self::NodeLabel>;
+    fn node_labels(&self, node_id: &Self::NodeId) -> LabelIterator<'_, &Self::NodeLabel>;
 
     fn node_properties(
         &self,
         node_id: &Self::NodeId,
-    ) -> PropertyIterator<&Self::PropertyKey, &Self::PropertyValue>;
+    ) -> PropertyIterator<'_, &Self::PropertyKey, &Self::PropertyValue>;
 
     fn outgoing_relationships<'a, 'b: 'a>(
         &'a self,
         node_id: &'b Self::NodeId,
-    ) -> PropertyIterator<
-        (&'a Self::NodeId, &'a Self::RelationshipType),
-        PropertyIterator<&'a Self::PropertyKey, &'a Self::PropertyValue>,
+    ) -> RelationshipIterator<
+        'a,
+        &'a Self::NodeId,
+        &'a Self::RelationshipType,
+        &'a Self::PropertyKey,
+        &'a Self::PropertyValue,
     >;
 
     fn incoming_relationships<'a, 'b: 'a>(
         &'a self,
         node_id: &'b Self::NodeId,
-    ) -> PropertyIterator<
-        (&'a Self::NodeId, &'a Self::RelationshipType),
-        PropertyIterator<&'a Self::PropertyKey, &'a Self::PropertyValue>,
+    ) -> RelationshipIterator<
+        'a,
+        &'a Self::NodeId,
+        &'a Self::RelationshipType,
+        &'a Self::PropertyKey,
+        &'a Self::PropertyValue,
     >;
+
+    /// Returns the number of nodes.
+    ///
+    /// Used to reject graphs of different sizes before canonicalizing them.
+    /// The default consumes `nodes`, adapters that know the count should
+    /// override it.
+    fn node_count(&self) -> usize {
+        self.nodes().count()
+    }
+
+    /// Returns the number of relationships.
+    ///
+    /// The default counts `relationships`, adapters that know the count
+    /// should override it.
+    fn relationship_count(&self) -> usize {
+        self.relationships().count()
+    }
+
+    /// Returns every relationship once, as source, target and type, and
+    /// properties.
+    ///
+    /// Canonicalization reads relationships through this method. The default
+    /// calls `outgoing_relationships` for every node, adapters that cannot
+    /// look up the relationships of a node cheaply should override it with
+    /// a single pass over their relationships.
+    fn relationships(
+        &self,
+    ) -> AllRelationshipsIterator<
+        '_,
+        &Self::NodeId,
+        &Self::RelationshipType,
+        &Self::PropertyKey,
+        &Self::PropertyValue,
+    > {
+        Box::new(self.nodes().flat_map(move |source| {
+            self.outgoing_relationships(source)
+                .map(move |(target, properties)| (source, target, properties))
+        }))
+    }
+
+    /// Returns a stable id of a relationship, if the graph has one.
+    ///
+    /// The relationship is the one at `position` among the relationships of
+    /// `source`, in the order in which `relationships` yields them, i.e., in
+    /// the order of `outgoing_relationships` unless `relationships` is
+    /// overridden. Ids are only read if `CompareOptions::use_relationship_ids`
+    /// is set. The default returns `None` for every relationship.
+    fn relationship_id(&self, _source: &Self::NodeId, _position: usize) -> Option<String> {
+        None
+    }
+
+    /// Whether the relationships of the graph are directed.
+    ///
+    /// The relationships of undirected graphs are compared as if
+    /// `CompareOptions::treat_as_undirected` was set.
+    fn is_directed(&self) -> bool {
+        true
+    }
+
+    /// Returns a typed view of a property value.
+    ///
+    /// Options that need more than the `Display` representation, such as
+    /// resolving conflicts between merged properties, work on this view.
+    /// By default, every value is treated as an opaque string, i.e., values
+    /// are compared by their `Display` representation. Adapters should
+    /// return the same `Value` for the same logical value, so that they can
+    /// be compared with other adapters.
+    fn property_value(&self, value: &Self::PropertyValue) -> Value {
+        Value::String(value.to_string())
+    }
+}
+
+/// Convenience methods available on every `Graph`.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::{canonicalize, GraphExt};
+///
+/// let graph = "(a:A)-[:R]->(b:B)".parse::<GdlGraph>().unwrap();
+///
+/// println!("{}", graph.canonical());
+/// assert_eq!(graph.canonical().to_string(), canonicalize(&graph));
+/// ```
+pub trait GraphExt: Graph {
+    /// Computes the canonical form of the graph, see `canonical_form`.
+    fn canonical(&self) -> CanonicalForm;
+}
+
+impl<G: Graph> GraphExt for G {
+    fn canonical(&self) -> CanonicalForm {
+        crate::canonical_form(self)
+    }
 }
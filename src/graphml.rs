@@ -0,0 +1,674 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    io::{self, Read},
+};
+
+use crate::{SimpleGraph, Value};
+
+/// A GraphML document that could not be read.
+#[derive(Debug)]
+pub enum GraphmlError {
+    Io(io::Error),
+    /// The document is not well-formed or not valid GraphML.
+    Invalid {
+        line: usize,
+        message: String,
+    },
+}
+
+impl Display for GraphmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphmlError::Io(error) => write!(f, "could not read GraphML: {}", error),
+            GraphmlError::Invalid { line, message } => {
+                write!(f, "invalid GraphML in line {}: {}", line, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GraphmlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GraphmlError::Io(error) => Some(error),
+            GraphmlError::Invalid { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for GraphmlError {
+    fn from(error: io::Error) -> Self {
+        GraphmlError::Io(error)
+    }
+}
+
+/// Reads the first graph of a GraphML document.
+///
+/// The `edgedefault` attribute of the graph decides whether the graph is
+/// directed, see `Graph::is_directed`. Nodes are identified by their `id`.
+/// Every `<data>` element becomes a property named by the `attr.name` of its
+/// `<key>`, converted to the declared `attr.type`. Keys with a `<default>`
+/// provide the property for all nodes or edges without a value.
+///
+/// GraphML has no labels or relationship types. Following the convention of
+/// graph database exports, a node property named `labels`, such as
+/// `:Person:Admin`, is read as the labels of the node and an edge property
+/// named `label` as the type of the relationship.
+///
+/// Nested graphs, hyperedges and edges that deviate from `edgedefault` are
+/// not supported. Elements of other namespaces, e.g., layout information
+/// inside `<data>`, are ignored.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::{equals, parse_graphml};
+///
+/// let graphml = r#"<?xml version="1.0" encoding="UTF-8"?>
+/// <graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+///   <key id="d0" for="node" attr.name="labels" attr.type="string"/>
+///   <key id="d1" for="edge" attr.name="weight" attr.type="int"/>
+///   <graph edgedefault="directed">
+///     <node id="n0"><data key="d0">:Person</data></node>
+///     <node id="n1"/>
+///     <edge source="n0" target="n1"><data key="d1">3</data></edge>
+///   </graph>
+/// </graphml>"#;
+///
+/// let expected = "(a:Person), (b), (a)-[{ weight: 3 }]->(b)".parse::<GdlGraph>().unwrap();
+///
+/// assert!(equals(&parse_graphml(graphml.as_bytes()).unwrap(), &expected));
+/// ```
+pub fn parse_graphml(mut reader: impl Read) -> Result<SimpleGraph, GraphmlError> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    Parser::default().document(&input)
+}
+
+#[derive(Debug)]
+enum Event {
+    Start {
+        name: String,
+        attributes: HashMap<String, String>,
+        empty: bool,
+    },
+    End {
+        name: String,
+    },
+    Text(String),
+}
+
+/// Splits a document into elements and text, skipping declarations,
+/// comments and processing instructions.
+fn events(input: &str) -> Result<Vec<(Event, usize)>, GraphmlError> {
+    let mut events = Vec::new();
+    let mut rest = input;
+    let mut line = 1;
+
+    while !rest.is_empty() {
+        let (consumed, event) = if rest.starts_with("<!--") {
+            (until(rest, "-->", line)?, None)
+        } else if let Some(data) = rest.strip_prefix("<![CDATA[") {
+            let end = until(data, "]]>", line)?;
+            (
+                end + "<![CDATA[".len(),
+                Some(Event::Text(data[..end - "]]>".len()].to_string())),
+            )
+        } else if rest.starts_with("<?") || rest.starts_with("<!") {
+            (until(rest, ">", line)?, None)
+        } else if let Some(tag) = rest.strip_prefix("</") {
+            let end = until(tag, ">", line)?;
+            let name = local_name(tag[..end - 1].trim());
+            (end + 2, Some(Event::End { name }))
+        } else if let Some(tag) = rest.strip_prefix('<') {
+            let end = tag_end(tag, line)?;
+            (end + 2, Some(start_tag(&tag[..end], line)?))
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let text = unescape(&rest[..end], line)?;
+            (end, (!text.trim().is_empty()).then_some(Event::Text(text)))
+        };
+
+        if let Some(event) = event {
+            events.push((event, line));
+        }
+        line += rest[..consumed].matches('\n').count();
+        rest = &rest[consumed..];
+    }
+
+    Ok(events)
+}
+
+/// Returns the length of `text` up to and including `end`.
+fn until(text: &str, end: &str, line: usize) -> Result<usize, GraphmlError> {
+    text.find(end)
+        .map(|position| position + end.len())
+        .ok_or_else(|| invalid(line, format!("missing {:?}", end)))
+}
+
+/// Returns the position of the `>` that closes a start tag, skipping quoted
+/// attribute values.
+fn tag_end(tag: &str, line: usize) -> Result<usize, GraphmlError> {
+    let mut quote = None;
+    for (position, c) in tag.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, '>') => return Ok(position),
+            _ => {}
+        }
+    }
+    Err(invalid(line, "unterminated tag"))
+}
+
+fn start_tag(tag: &str, line: usize) -> Result<Event, GraphmlError> {
+    let (tag, empty) = match tag.strip_suffix('/') {
+        Some(tag) => (tag, true),
+        None => (tag, false),
+    };
+    let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+    let name = local_name(&tag[..name_end]);
+
+    let mut attributes = HashMap::new();
+    let mut rest = tag[name_end..].trim_start();
+    while !rest.is_empty() {
+        let equals = rest
+            .find('=')
+            .ok_or_else(|| invalid(line, format!("attribute without value in <{}>", name)))?;
+        let key = local_name(rest[..equals].trim());
+        let value = rest[equals + 1..].trim_start();
+        let quote = value
+            .chars()
+            .next()
+            .filter(|&c| c == '"' || c == '\'')
+            .ok_or_else(|| invalid(line, format!("unquoted attribute {} in <{}>", key, name)))?;
+        let end = value[1..]
+            .find(quote)
+            .ok_or_else(|| invalid(line, "unterminated attribute value"))?;
+        attributes.insert(key, unescape(&value[1..end + 1], line)?);
+        rest = value[end + 2..].trim_start();
+    }
+
+    Ok(Event::Start {
+        name,
+        attributes,
+        empty,
+    })
+}
+
+/// Drops the namespace prefix of a name.
+fn local_name(name: &str) -> String {
+    name.rsplit(':').next().unwrap_or(name).to_string()
+}
+
+fn unescape(text: &str, line: usize) -> Result<String, GraphmlError> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .ok_or_else(|| invalid(line, "unterminated entity"))?;
+        let entity = &rest[start + 1..start + end];
+        let c = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match entity.strip_prefix("#x") {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => entity.strip_prefix('#').and_then(|dec| dec.parse().ok()),
+            }
+            .and_then(char::from_u32),
+        };
+        unescaped.push(c.ok_or_else(|| invalid(line, format!("unknown entity &{};", entity)))?);
+        rest = &rest[start + end + 1..];
+    }
+    unescaped.push_str(rest);
+    Ok(unescaped)
+}
+
+fn invalid(line: usize, message: impl Into<String>) -> GraphmlError {
+    GraphmlError::Invalid {
+        line,
+        message: message.into(),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Domain {
+    Node,
+    Edge,
+    Other,
+}
+
+struct Key {
+    name: String,
+    domain: Option<Domain>,
+    attr_type: String,
+    default: Option<String>,
+}
+
+impl Key {
+    fn applies_to(&self, domain: Domain) -> bool {
+        self.domain.map_or(true, |key_domain| key_domain == domain)
+    }
+
+    fn value(&self, text: &str, line: usize) -> Result<Value, GraphmlError> {
+        let text = text.trim();
+        let invalid_value = || {
+            invalid(
+                line,
+                format!(
+                    "{:?} is not a valid {} for {}",
+                    text, self.attr_type, self.name
+                ),
+            )
+        };
+        Ok(match self.attr_type.as_str() {
+            "boolean" => Value::Boolean(text.parse().map_err(|_| invalid_value())?),
+            "int" | "long" => Value::Integer(text.parse().map_err(|_| invalid_value())?),
+            "float" | "double" => Value::Float(text.parse().map_err(|_| invalid_value())?),
+            _ => Value::String(text.to_string()),
+        })
+    }
+}
+
+type Properties = Vec<(String, Value)>;
+
+#[derive(Default)]
+struct Parser {
+    keys: HashMap<String, Key>,
+    /// Keys in declaration order, to apply defaults deterministically.
+    key_order: Vec<String>,
+    directed: Option<bool>,
+    nodes: Vec<(String, Properties)>,
+    edges: Vec<(String, String, Properties, usize)>,
+}
+
+/// The element whose `<data>` children are currently read.
+enum Owner {
+    Node(usize),
+    Edge(usize),
+}
+
+impl Parser {
+    fn document(mut self, input: &str) -> Result<SimpleGraph, GraphmlError> {
+        let mut events = events(input)?.into_iter();
+
+        let mut graphs = 0;
+        let mut owner = None;
+
+        while let Some((event, line)) = events.next() {
+            let (name, attributes, empty) = match event {
+                Event::Start {
+                    name,
+                    attributes,
+                    empty,
+                } => (name, attributes, empty),
+                Event::End { name } => {
+                    if name == "node" || name == "edge" {
+                        owner = None;
+                    }
+                    continue;
+                }
+                Event::Text(_) => continue,
+            };
+
+            let attribute = |key: &str| {
+                attributes
+                    .get(key)
+                    .cloned()
+                    .ok_or_else(|| invalid(line, format!("<{}> without {}", name, key)))
+            };
+
+            match name.as_str() {
+                "graphml" => {}
+                "key" => {
+                    let id = attribute("id")?;
+                    let key = Key {
+                        name: attributes
+                            .get("attr.name")
+                            .cloned()
+                            .unwrap_or_else(|| id.clone()),
+                        domain: match attributes.get("for").map(String::as_str) {
+                            None | Some("all") => None,
+                            Some("node") => Some(Domain::Node),
+                            Some("edge") => Some(Domain::Edge),
+                            Some(_) => Some(Domain::Other),
+                        },
+                        attr_type: attributes
+                            .get("attr.type")
+                            .cloned()
+                            .unwrap_or_else(|| "string".to_string()),
+                        default: None,
+                    };
+                    self.key_order.push(id.clone());
+                    self.keys.insert(id, key);
+                }
+                "default" => {
+                    let text = text(&mut events, empty);
+                    if let Some(key) = self.key_order.last() {
+                        self.keys.get_mut(key).unwrap().default = Some(text);
+                    }
+                }
+                "graph" if owner.is_some() => {
+                    return Err(invalid(line, "nested graphs are not supported"))
+                }
+                // Only the first graph of a document is read.
+                "graph" if graphs > 0 => skip(&mut events, empty),
+                "graph" => {
+                    graphs += 1;
+                    self.directed = Some(match attributes.get("edgedefault").map(String::as_str) {
+                        Some("undirected") => false,
+                        Some("directed") => true,
+                        Some(other) => {
+                            return Err(invalid(line, format!("unknown edgedefault {:?}", other)))
+                        }
+                        None => return Err(invalid(line, "<graph> without edgedefault")),
+                    });
+                }
+                "node" => {
+                    self.nodes.push((attribute("id")?, Properties::new()));
+                    owner = (!empty).then_some(Owner::Node(self.nodes.len() - 1));
+                }
+                "edge" => {
+                    if let Some(directed) = attributes.get("directed") {
+                        if Some(directed == "true") != self.directed {
+                            return Err(invalid(
+                                line,
+                                "edges that deviate from edgedefault are not supported",
+                            ));
+                        }
+                    }
+                    self.edges.push((
+                        attribute("source")?,
+                        attribute("target")?,
+                        Properties::new(),
+                        line,
+                    ));
+                    owner = (!empty).then_some(Owner::Edge(self.edges.len() - 1));
+                }
+                "hyperedge" => return Err(invalid(line, "hyperedges are not supported")),
+                "data" => {
+                    let key_id = attribute("key")?;
+                    let text = text(&mut events, empty);
+                    let (properties, domain, owners) = match &owner {
+                        Some(Owner::Node(node)) => {
+                            (&mut self.nodes[*node].1, Domain::Node, "nodes")
+                        }
+                        Some(Owner::Edge(edge)) => {
+                            (&mut self.edges[*edge].2, Domain::Edge, "edges")
+                        }
+                        // Data of the graph or the document.
+                        None => continue,
+                    };
+                    let key = self
+                        .keys
+                        .get(&key_id)
+                        .filter(|key| key.applies_to(domain))
+                        .ok_or_else(|| {
+                            invalid(
+                                line,
+                                format!("key {:?} is not declared for {}", key_id, owners),
+                            )
+                        })?;
+                    properties.push((key.name.clone(), key.value(&text, line)?));
+                }
+                // Elements of other namespaces, e.g., layout information.
+                _ => skip(&mut events, empty),
+            }
+        }
+
+        self.build()
+    }
+
+    fn build(self) -> Result<SimpleGraph, GraphmlError> {
+        let mut graph = match self.directed {
+            Some(true) => SimpleGraph::new(),
+            Some(false) => SimpleGraph::new_undirected(),
+            None => return Err(invalid(1, "no <graph> element")),
+        };
+
+        let defaults = |domain| -> Result<Properties, GraphmlError> {
+            self.key_order
+                .iter()
+                .map(|id| &self.keys[id])
+                .filter(|key| key.applies_to(domain))
+                .filter_map(|key| key.default.as_ref().map(|default| (key, default)))
+                .map(|(key, default)| Ok((key.name.clone(), key.value(default, 1)?)))
+                .collect()
+        };
+        let (node_defaults, edge_defaults) = (defaults(Domain::Node)?, defaults(Domain::Edge)?);
+
+        let mut ids = HashMap::new();
+        for (id, properties) in &self.nodes {
+            if ids.insert(id.as_str(), ()).is_some() {
+                return Err(invalid(1, format!("duplicate node id {:?}", id)));
+            }
+            let (labels, properties) = with_defaults(properties, &node_defaults)
+                .into_iter()
+                .partition::<Vec<_>, _>(|(key, _)| key == "labels");
+            let labels = labels.into_iter().flat_map(|(_, labels)| {
+                labels
+                    .to_string()
+                    .split(':')
+                    .filter(|label| !label.is_empty())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            });
+            graph.add_node(id.clone(), labels, properties);
+        }
+
+        for (source, target, properties, line) in &self.edges {
+            if let Some(missing) = [source, target]
+                .iter()
+                .find(|id| !ids.contains_key(id.as_str()))
+            {
+                return Err(invalid(
+                    *line,
+                    format!("edge to unknown node {:?}", missing),
+                ));
+            }
+            let (rel_type, properties) = with_defaults(properties, &edge_defaults)
+                .into_iter()
+                .partition::<Vec<_>, _>(|(key, _)| key == "label");
+            let rel_type = rel_type
+                .into_iter()
+                .map(|(_, rel_type)| rel_type.to_string())
+                .next()
+                .unwrap_or_default();
+            graph.add_edge(source, target, rel_type, properties);
+        }
+
+        Ok(graph)
+    }
+}
+
+/// Adds the default of every key that has no value yet.
+fn with_defaults(properties: &[(String, Value)], defaults: &[(String, Value)]) -> Properties {
+    let mut properties = properties.to_vec();
+    defaults.iter().for_each(|(key, value)| {
+        if properties.iter().all(|(existing, _)| existing != key) {
+            properties.push((key.clone(), value.clone()));
+        }
+    });
+    properties
+}
+
+type Events = std::vec::IntoIter<(Event, usize)>;
+
+/// Reads the text content of an element up to its end tag, ignoring nested elements.
+fn text(events: &mut Events, empty: bool) -> String {
+    let mut text = String::new();
+    if empty {
+        return text;
+    }
+    let mut depth = 0;
+    for (event, _) in events.by_ref() {
+        match event {
+            Event::Text(content) => text.push_str(&content),
+            Event::Start { empty: false, .. } => depth += 1,
+            Event::Start { .. } => {}
+            Event::End { .. } if depth == 0 => break,
+            Event::End { .. } => depth -= 1,
+        }
+    }
+    text
+}
+
+/// Skips the content of an element up to its end tag.
+fn skip(events: &mut Events, empty: bool) {
+    text(events, empty);
+}
+
+#[cfg(all(feature = "gdl", test))]
+mod tests {
+    use super::*;
+    use crate::{canonicalize, equals, Graph};
+
+    use ::gdl::Graph as GdlGraph;
+
+    const KEYS: &str = r#"
+        <key id="labels" for="node" attr.name="labels" attr.type="string"/>
+        <key id="name" for="node" attr.name="name" attr.type="string"/>
+        <key id="active" for="node" attr.name="active" attr.type="boolean">
+          <default>false</default>
+        </key>
+        <key id="type" for="edge" attr.name="label" attr.type="string"/>
+        <key id="since" for="edge" attr.name="since" attr.type="long"/>
+        <key id="weight" for="all" attr.name="weight" attr.type="double"/>
+    "#;
+
+    fn document(edgedefault: &str, graph: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <!-- exported by a pipeline -->
+            <graphml xmlns="http://graphml.graphdrawing.org/xmlns"
+                     xmlns:y="http://www.yworks.com/xml/graphml">
+              {}
+              <graph id="G" edgedefault="{}">
+                {}
+              </graph>
+            </graphml>"#,
+            KEYS, edgedefault, graph
+        )
+    }
+
+    fn parse(edgedefault: &str, graph: &str) -> SimpleGraph {
+        parse_graphml(document(edgedefault, graph).as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_graphml() {
+        let graph = parse(
+            "directed",
+            r#"
+            <node id="n0">
+              <data key="labels">:Person:Admin</data>
+              <data key="name">Alice &amp; Bob</data>
+              <data key="active">true</data>
+            </node>
+            <node id="n1">
+              <data key="labels">:Person</data>
+              <data key="weight"><y:ShapeNode><y:Fill color="yellow"/></y:ShapeNode>1.5</data>
+            </node>
+            <edge source="n0" target="n1">
+              <data key="type">KNOWS</data>
+              <data key="since">2020</data>
+            </edge>
+            <edge id="e1" source="n1" target="n1"/>
+            "#,
+        );
+
+        let expected = "
+            (a:Person:Admin { name: 'Alice & Bob', active: true }),
+            (b:Person { weight: 1.5, active: false }),
+            (a)-[:KNOWS { since: 2020 }]->(b), (b)-->(b)
+        "
+        .parse::<GdlGraph>()
+        .unwrap();
+
+        assert!(graph.is_directed());
+        assert_eq!(canonicalize(&graph), canonicalize(&expected));
+    }
+
+    #[test]
+    fn test_isomorphic_up_to_node_ids() {
+        let g1 = parse(
+            "directed",
+            r#"
+            <node id="a"><data key="name">x</data></node>
+            <node id="b"/>
+            <node id="c"/>
+            <edge source="a" target="b"><data key="weight">0.5</data></edge>
+            <edge source="b" target="c"/>
+            "#,
+        );
+        let g2 = parse(
+            "directed",
+            r#"
+            <node id="n2"/>
+            <node id="n1"/>
+            <edge source="n1" target="n2"/>
+            <edge source="n0" target="n1"><data key="weight">0.5</data></edge>
+            <node id="n0"><data key="name">x</data></node>
+            "#,
+        );
+
+        assert!(equals(&g1, &g2));
+    }
+
+    #[test]
+    fn test_edgedefault() {
+        let g1 = parse(
+            "undirected",
+            r#"<node id="a"/><node id="b"/><edge source="a" target="b"/>"#,
+        );
+        let g2 = parse(
+            "undirected",
+            r#"<node id="a"/><node id="b"/><edge source="b" target="a"/>"#,
+        );
+        let g3 = parse(
+            "directed",
+            r#"<node id="a"/><node id="b"/><edge source="b" target="a"/>"#,
+        );
+
+        assert!(!g1.is_directed());
+        assert!(equals(&g1, &g2));
+        assert!(!equals(&g1, &g3));
+    }
+
+    #[test]
+    fn test_parse_graphml_errors() {
+        let message = |graph| {
+            parse_graphml(document("directed", graph).as_bytes())
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            message(r#"<node id="a"><data key="since">1</data></node>"#),
+            "invalid GraphML in line 16: key \"since\" is not declared for nodes"
+        );
+        assert_eq!(
+            message(r#"<node id="a"><data key="active">yes</data></node>"#),
+            "invalid GraphML in line 16: \"yes\" is not a valid boolean for active"
+        );
+        assert_eq!(
+            message(r#"<node id="a"/><edge source="a" target="b"/>"#),
+            "invalid GraphML in line 16: edge to unknown node \"b\""
+        );
+        assert_eq!(
+            message(r#"<node id="a"/><edge source="a" target="a" directed="false"/>"#),
+            "invalid GraphML in line 16: edges that deviate from edgedefault are not supported"
+        );
+        assert_eq!(
+            parse_graphml("<graphml/>".as_bytes())
+                .unwrap_err()
+                .to_string(),
+            "invalid GraphML in line 1: no <graph> element"
+        );
+    }
+}
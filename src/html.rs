@@ -0,0 +1,95 @@
+use std::fmt::Write;
+
+use crate::{
+    diff::{aligned_rows, AlignedRow},
+    Graph,
+};
+
+const STYLE: &str = "table{border-collapse:collapse;font-family:monospace}\
+td{padding:2px 8px;vertical-align:top;white-space:pre}\
+.removed{background:#fdd}.added{background:#dfd}";
+
+/// Renders the canonical rows of both graphs as a self-contained HTML table.
+///
+/// Rows are aligned side by side: shared rows appear in both columns, rows
+/// only in `left` are marked with the `removed` class, rows only in `right`
+/// with the `added` class.
+pub fn format_graph_diff_html(left: &impl Graph, right: &impl Graph) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    writeln!(html, "<style>{}</style>", STYLE).unwrap();
+    html.push_str("</head>\n<body>\n<table>\n<tr><th>left</th><th>right</th></tr>\n");
+
+    aligned_rows(left, right).into_iter().for_each(|row| {
+        let (class, left, right) = match &row {
+            AlignedRow::Shared(row) => ("shared", row.as_str(), row.as_str()),
+            AlignedRow::Removed(row) => ("removed", row.as_str(), ""),
+            AlignedRow::Added(row) => ("added", "", row.as_str()),
+        };
+        writeln!(
+            html,
+            "<tr class=\"{}\"><td>{}</td><td>{}</td></tr>",
+            class,
+            escape(left),
+            escape(right)
+        )
+        .unwrap();
+    });
+
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+fn escape(text: &str) -> String {
+    text.chars()
+        .fold(String::with_capacity(text.len()), |mut escaped, c| {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                c => escaped.push(c),
+            }
+            escaped
+        })
+}
+
+#[cfg(all(feature = "gdl", test))]
+mod tests {
+    use super::*;
+
+    use ::gdl::Graph as GdlGraph;
+
+    fn from_gdl(gdl: &str) -> GdlGraph {
+        gdl.parse::<GdlGraph>().unwrap()
+    }
+
+    #[test]
+    fn test_format_graph_diff_html() {
+        let left = from_gdl("(a:A), (b:B), (a)-->(b)");
+        let right = from_gdl("(b:B), (c:C)");
+
+        let html = format_graph_diff_html(&left, &right);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains(
+            "<tr class=\"removed\"><td>(:A ) =&gt; out: ()-[: ]-&gt;(:B ) in: </td><td></td></tr>"
+        ));
+        assert!(html.contains(
+            "<tr class=\"removed\"><td>(:B ) =&gt; out:  in: ()&lt;-[: ]-(:A )</td><td></td></tr>"
+        ));
+        assert!(html.contains("<tr class=\"added\"><td></td><td>(:B ) =&gt; out:  in: </td></tr>"));
+        assert!(html.contains("<tr class=\"added\"><td></td><td>(:C ) =&gt; out:  in: </td></tr>"));
+    }
+
+    #[test]
+    fn test_format_graph_diff_html_shared_rows() {
+        let g = from_gdl("(a:A)");
+
+        let html = format_graph_diff_html(&g, &g);
+
+        assert!(html.contains("<tr class=\"shared\"><td>(:A ) =&gt; out:  in: </td><td>(:A ) =&gt; out:  in: </td></tr>"));
+        assert!(!html.contains("class=\"added\""));
+        assert!(!html.contains("class=\"removed\""));
+    }
+}
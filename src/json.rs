@@ -0,0 +1,93 @@
+use serde_json::{json, Value as JsonValue};
+
+use crate::{canonical::canonical_adjacencies, CompareOptions, Graph};
+
+/// Returns the canonical rows of a graph as a JSON array.
+///
+/// Every row is an object `{"node": ..., "out": [...], "in": [...]}` with
+/// the canonical node and the sorted tokens of its outgoing and incoming
+/// relationships, as they appear in `canonicalize`. Rows are sorted by
+/// node, then by their relationships, so isomorphic graphs produce equal
+/// arrays. Unlike `canonicalize`, the refinement rounds are not included,
+/// which only matters for graphs whose rows do not tell them apart, e.g.,
+/// regular graphs.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::canonical_json;
+/// use serde_json::json;
+///
+/// let graph = "(a:A)-[:R]->(b:B)".parse::<GdlGraph>().unwrap();
+///
+/// assert_eq!(
+///     canonical_json(&graph),
+///     json!([
+///         {"node": "(:A )", "out": ["()-[:R ]->(:B )"], "in": []},
+///         {"node": "(:B )", "out": [], "in": ["()<-[:R ]-(:A )"]},
+///     ])
+/// );
+/// ```
+pub fn canonical_json<G: Graph>(graph: &G) -> JsonValue {
+    canonical_adjacencies(graph, &CompareOptions::default())
+        .into_iter()
+        .map(|(node, out, incoming)| json!({"node": node, "out": out, "in": incoming}))
+        .collect()
+}
+
+#[cfg(all(feature = "gdl", test))]
+mod tests {
+    use super::*;
+    use crate::canonicalize;
+
+    use ::gdl::Graph as GdlGraph;
+
+    fn from_gdl(gdl: &str) -> GdlGraph {
+        gdl.parse::<GdlGraph>().unwrap()
+    }
+
+    #[test]
+    fn test_isomorphic_graphs_have_equal_json() {
+        let g1 = from_gdl("(a:A {v: 1}), (b:B), (c), (a)-[:R {w: 2}]->(b), (b)-->(c), (c)-->(a)");
+        let g2 = from_gdl("(z), (y:B), (x:A {v: 1}), (y)-->(z), (z)-->(x), (x)-[:R {w: 2}]->(y)");
+
+        assert_eq!(canonical_json(&g1), canonical_json(&g2));
+        assert_eq!(
+            canonical_json(&g1).to_string(),
+            canonical_json(&g2).to_string()
+        );
+        assert_ne!(
+            canonical_json(&g1),
+            canonical_json(&from_gdl("(a:A {v: 1}), (b:B), (c), (a)-[:R {w: 2}]->(b)"))
+        );
+    }
+
+    #[test]
+    fn test_json_matches_canonical_rows() {
+        let graph = from_gdl("(a:A)-->(b:B)-->(a), (b)-[:S]->(c:C), (c)-[:S]->(c)");
+        let rows = canonical_json(&graph)
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|row| {
+                let tokens = |key: &str| {
+                    row[key]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .map(|token| token.as_str().unwrap())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                format!(
+                    "{} => out: {} in: {}",
+                    row["node"].as_str().unwrap(),
+                    tokens("out"),
+                    tokens("in")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        assert_eq!(rows, canonicalize(&graph));
+    }
+}
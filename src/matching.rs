@@ -0,0 +1,978 @@
+use std::{cell::Cell, collections::HashMap};
+
+use crate::{
+    canonical::{collect, property_tokens, Collected},
+    error::BudgetExceeded,
+    CompareOptions, Graph, LabelMatch,
+};
+
+/// Which embeddings of a pattern are counted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Embeddings {
+    /// Embeddings that differ only by an automorphism of the pattern are
+    /// counted once, e.g., a triangle is counted once, no matter how many
+    /// ways the pattern can be rotated onto it.
+    #[default]
+    Distinct,
+    /// Every injective mapping of pattern nodes to host nodes is counted.
+    Raw,
+}
+
+/// Counts the automorphism-distinct embeddings of `pattern` in `host`.
+///
+/// See `count_pattern_with` for how pattern nodes and relationships are matched.
+pub fn count_pattern<P: Graph, G: Graph>(pattern: &P, host: &G) -> usize {
+    count_pattern_with(pattern, host, Embeddings::Distinct)
+}
+
+/// Counts the embeddings of `pattern` in `host`.
+///
+/// An embedding maps every pattern node to a distinct host node whose labels
+/// and properties are a superset of the pattern node's labels and properties.
+/// Every pattern relationship must map to a distinct host relationship
+/// between the mapped nodes with the same type and a superset of its
+/// properties. Host nodes and relationships not covered by the embedding
+/// are ignored, i.e., the pattern does not need to be an induced subgraph.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::{count_pattern, count_pattern_with, Embeddings};
+///
+/// let host = "(a)-->(b)-->(c)-->(a), (c)-->(d)".parse::<GdlGraph>().unwrap();
+/// let triangle = "(x)-->(y)-->(z)-->(x)".parse::<GdlGraph>().unwrap();
+///
+/// assert_eq!(count_pattern(&triangle, &host), 1);
+/// assert_eq!(count_pattern_with(&triangle, &host, Embeddings::Raw), 3);
+/// ```
+pub fn count_pattern_with<P: Graph, G: Graph>(
+    pattern: &P,
+    host: &G,
+    embeddings: Embeddings,
+) -> usize {
+    let pattern = Prepared::new(&Collected::new(pattern));
+    let host = Prepared::new(&Collected::new(host));
+
+    let raw = Matcher::new(&pattern, &host, false).count();
+    match embeddings {
+        Embeddings::Raw => raw,
+        Embeddings::Distinct if raw == 0 => 0,
+        Embeddings::Distinct => raw / Matcher::new(&pattern, &pattern, true).count(),
+    }
+}
+
+/// Counts the automorphisms of a graph, i.e., the bijections of its nodes
+/// onto themselves that preserve labels, properties and relationships.
+///
+/// Every graph has at least one automorphism, the identity. The count is
+/// found by the same search as `assert_graph_iso_exact`, matching the graph
+/// against itself, and takes exponential time in the worst case.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::automorphism_count;
+///
+/// let cycle = "(a)-->(b)-->(c)-->(a)".parse::<GdlGraph>().unwrap();
+/// let star = "(c)-->(a), (c)-->(b), (c)-->(d)".parse::<GdlGraph>().unwrap();
+///
+/// assert_eq!(automorphism_count(&cycle), 3);
+/// assert_eq!(automorphism_count(&star), 6);
+/// ```
+pub fn automorphism_count<G: Graph>(graph: &G) -> usize {
+    let graph = Prepared::new(&Collected::new(graph));
+    Matcher::new(&graph, &graph, true).count()
+}
+
+/// Checks whether `pattern` embeds into `graph`, see `count_pattern_with`
+/// for how pattern nodes and relationships are matched.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::is_subgraph_of;
+///
+/// let graph = "(a:A:B {v: 1}), (b:B), (a)-[:R {w: 1}]->(b)".parse::<GdlGraph>().unwrap();
+/// let pattern = "(x:A), (y), (x)-[:R]->(y)".parse::<GdlGraph>().unwrap();
+///
+/// assert!(is_subgraph_of(&pattern, &graph));
+/// assert!(!is_subgraph_of(&graph, &pattern));
+/// ```
+pub fn is_subgraph_of<P: Graph, G: Graph>(pattern: &P, graph: &G) -> bool {
+    let pattern = Prepared::new(&Collected::new(pattern));
+    let graph = Prepared::new(&Collected::new(graph));
+    embeds(&Matcher::new(&pattern, &graph, false))
+}
+
+/// Like `is_subgraph_of`, but reads both graphs under the given options and
+/// matches labels according to `CompareOptions::label_match`. With the
+/// default `LabelMatch::Exact`, pattern nodes only match nodes with the
+/// same labels, use `LabelMatch::Subset` to match like `is_subgraph_of`.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::{is_subgraph_of_with, CompareOptions, LabelMatch};
+///
+/// let graph = "(a:A:B)-[:R]->(b:B)".parse::<GdlGraph>().unwrap();
+/// let pattern = "(x:A)-[:R]->(y:B)".parse::<GdlGraph>().unwrap();
+///
+/// let exact = CompareOptions::new();
+/// let subset = CompareOptions::new().label_match(LabelMatch::Subset);
+/// assert!(!is_subgraph_of_with(&pattern, &graph, &exact));
+/// assert!(is_subgraph_of_with(&pattern, &graph, &subset));
+/// ```
+pub fn is_subgraph_of_with<P: Graph, G: Graph>(
+    pattern: &P,
+    graph: &G,
+    options: &CompareOptions,
+) -> bool {
+    let pattern = Prepared::new(&collect(pattern, options));
+    let graph = Prepared::new(&collect(graph, options));
+    embeds(&Matcher::with_label_match(
+        &pattern,
+        &graph,
+        false,
+        options.label_match,
+    ))
+}
+
+fn embeds(matcher: &Matcher<'_>) -> bool {
+    let mut found = false;
+    matcher.for_each(|_| {
+        found = true;
+        false
+    });
+    found
+}
+
+/// Checks whether two graphs are isomorphic by searching for a bijection
+/// between their nodes.
+///
+/// Unlike `equals`, which compares canonical forms, this is exact: it never
+/// reports non-isomorphic graphs as equal. Mapped nodes must have the same
+/// labels and properties, and every pair of mapped nodes must be connected
+/// by relationships with the same types and properties. The search is a
+/// backtracking search in the spirit of VF2 and takes exponential time in
+/// the worst case, so prefer `equals` for large graphs.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::{assert_graph_iso_exact, equals};
+///
+/// let six_cycle = "(a)-->(b)-->(c)-->(d)-->(e)-->(f)-->(a)".parse::<GdlGraph>().unwrap();
+/// let two_triangles = "(a)-->(b)-->(c)-->(a), (d)-->(e)-->(f)-->(d)".parse::<GdlGraph>().unwrap();
+///
+/// assert!(equals(&six_cycle, &two_triangles));
+/// assert!(!assert_graph_iso_exact(&six_cycle, &two_triangles));
+/// ```
+pub fn assert_graph_iso_exact<L: Graph, R: Graph>(left: &L, right: &R) -> bool {
+    graph_isomorphism(left, right).is_some()
+}
+
+/// Like `assert_graph_iso_exact`, but compares the graphs under the given
+/// options, matching labels according to `CompareOptions::label_match`, and
+/// gives up once the search exceeds the budget given by
+/// `CompareOptions::max_nodes` and `CompareOptions::max_iterations`.
+///
+/// Only the exact search needs a budget: `equals` and the other
+/// comparisons of canonical forms run in polynomial time, see
+/// `equals_within_time` to bound their running time anyway.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::{assert_graph_iso_exact_within, BudgetExceeded, CompareOptions};
+///
+/// let g1 = "(a)-->(b)-->(c)".parse::<GdlGraph>().unwrap();
+/// let g2 = "(x)<--(y)<--(z)".parse::<GdlGraph>().unwrap();
+///
+/// let options = CompareOptions::new().max_iterations(100);
+/// assert_eq!(assert_graph_iso_exact_within(&g1, &g2, &options), Ok(true));
+///
+/// let options = CompareOptions::new().max_nodes(2);
+/// assert_eq!(
+///     assert_graph_iso_exact_within(&g1, &g2, &options),
+///     Err(BudgetExceeded::Nodes { limit: 2, actual: 3 })
+/// );
+/// ```
+pub fn assert_graph_iso_exact_within<L: Graph, R: Graph>(
+    left: &L,
+    right: &R,
+    options: &CompareOptions,
+) -> Result<bool, BudgetExceeded> {
+    let left = collect(left, options);
+    let right = collect(right, options);
+    if let Some(limit) = options.max_nodes {
+        let actual = left.nodes.len().max(right.nodes.len());
+        if actual > limit {
+            return Err(BudgetExceeded::Nodes { limit, actual });
+        }
+    }
+
+    let (left, right) = (Prepared::new(&left), Prepared::new(&right));
+    let mut matcher = Matcher::with_label_match(&left, &right, true, options.label_match);
+    matcher.max_iterations = options.max_iterations;
+
+    let mut found = false;
+    matcher.for_each(|_| {
+        found = true;
+        false
+    });
+    match options.max_iterations {
+        Some(limit) if matcher.exhausted.get() => Err(BudgetExceeded::Iterations { limit }),
+        _ => Ok(found),
+    }
+}
+
+/// The outcome of `compare`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IsoResult {
+    /// A bijection between the nodes of both graphs was found.
+    Isomorphic,
+    /// The graphs differ, either in their canonical forms or because the
+    /// exact search found no bijection.
+    NotIsomorphic,
+    /// The canonical forms are equal, but the exact search exceeded the
+    /// budget given by the options before finding a bijection.
+    Undecided,
+}
+
+impl IsoResult {
+    /// Returns true only for `Isomorphic`, like `equals` does for graphs
+    /// with equal canonical forms.
+    pub fn is_isomorphic(self) -> bool {
+        self == IsoResult::Isomorphic
+    }
+}
+
+/// Compares two graphs under the given options and tells apart graphs that
+/// are not isomorphic from graphs that could not be decided.
+///
+/// Graphs with different canonical forms are not isomorphic. Graphs with
+/// equal canonical forms are confirmed by the exact search of
+/// `assert_graph_iso_exact_within`, which is `Undecided` if it exceeds
+/// `CompareOptions::max_nodes` or `CompareOptions::max_iterations`. Without
+/// a budget, the result is never `Undecided`.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::{compare, CompareOptions, IsoResult};
+///
+/// let six_cycle = "(a)-->(b)-->(c)-->(d)-->(e)-->(f)-->(a)".parse::<GdlGraph>().unwrap();
+/// let two_triangles = "(a)-->(b)-->(c)-->(a), (d)-->(e)-->(f)-->(d)".parse::<GdlGraph>().unwrap();
+///
+/// let options = CompareOptions::new();
+/// assert_eq!(compare(&six_cycle, &six_cycle, &options), IsoResult::Isomorphic);
+/// assert_eq!(compare(&six_cycle, &two_triangles, &options), IsoResult::NotIsomorphic);
+///
+/// let options = CompareOptions::new().max_nodes(5);
+/// assert_eq!(compare(&six_cycle, &six_cycle, &options), IsoResult::Undecided);
+/// ```
+pub fn compare<L: Graph, R: Graph>(left: &L, right: &R, options: &CompareOptions) -> IsoResult {
+    if !crate::equals_with(left, right, options) {
+        return IsoResult::NotIsomorphic;
+    }
+    match assert_graph_iso_exact_within(left, right, options) {
+        Ok(true) => IsoResult::Isomorphic,
+        Ok(false) => IsoResult::NotIsomorphic,
+        Err(_) => IsoResult::Undecided,
+    }
+}
+
+/// Returns a bijection between the nodes of two isomorphic graphs as
+/// found by `assert_graph_iso_exact`, `None` if the graphs are not isomorphic.
+///
+/// If the graphs have automorphisms, any one of the valid bijections is returned.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::graph_isomorphism;
+///
+/// let g1 = "(a:A), (b:B), (a)-->(b)".parse::<GdlGraph>().unwrap();
+/// let g2 = "(x:B), (y:A), (y)-->(x)".parse::<GdlGraph>().unwrap();
+///
+/// let mapping = graph_isomorphism(&g1, &g2).unwrap();
+/// assert_eq!(mapping["a"], "y");
+/// assert_eq!(mapping["b"], "x");
+/// ```
+pub fn graph_isomorphism<'l, 'r, L: Graph, R: Graph>(
+    left: &'l L,
+    right: &'r R,
+) -> Option<HashMap<&'l L::NodeId, &'r R::NodeId>> {
+    let left = Collected::new(left);
+    let right = Collected::new(right);
+    let (left_prepared, right_prepared) = (Prepared::new(&left), Prepared::new(&right));
+
+    let mut bijection = None;
+    Matcher::new(&left_prepared, &right_prepared, true).for_each(|mapping| {
+        bijection = Some(
+            mapping
+                .iter()
+                .enumerate()
+                .map(|(node, &image)| (left.nodes[node].id, right.nodes[image].id))
+                .collect(),
+        );
+        false
+    });
+    bijection
+}
+
+/// A graph prepared for matching, with labels and properties rendered as
+/// sorted tokens and relationships grouped by their endpoints.
+pub(crate) struct Prepared {
+    nodes: Vec<NodeTokens>,
+    relationships: Vec<RelationshipTokens>,
+    between: HashMap<(usize, usize), Vec<usize>>,
+}
+
+struct NodeTokens {
+    labels: Vec<String>,
+    properties: Vec<String>,
+    out_degree: usize,
+    in_degree: usize,
+}
+
+struct RelationshipTokens {
+    rel_type: String,
+    properties: Vec<String>,
+    undirected: bool,
+}
+
+impl Prepared {
+    pub(crate) fn new<Id: ?Sized>(graph: &Collected<'_, Id>) -> Self {
+        let mut nodes = graph
+            .nodes
+            .iter()
+            .map(|node| {
+                let mut labels = node.labels.clone();
+                labels.sort();
+                if !graph.labels_as_multiset {
+                    labels.dedup();
+                }
+                NodeTokens {
+                    labels,
+                    properties: property_tokens(&node.properties),
+                    out_degree: 0,
+                    in_degree: 0,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut between = HashMap::<_, Vec<_>>::new();
+        let relationships = graph
+            .relationships
+            .iter()
+            .enumerate()
+            .map(|(position, rel)| {
+                nodes[rel.source].out_degree += 1;
+                nodes[rel.target].in_degree += 1;
+                between
+                    .entry((rel.source, rel.target))
+                    .or_default()
+                    .push(position);
+                // An undirected relationship connects its endpoints both ways.
+                if rel.undirected && rel.source != rel.target {
+                    nodes[rel.target].out_degree += 1;
+                    nodes[rel.source].in_degree += 1;
+                    between
+                        .entry((rel.target, rel.source))
+                        .or_default()
+                        .push(position);
+                }
+                RelationshipTokens {
+                    rel_type: rel.type_and_id().to_string(),
+                    properties: property_tokens(&rel.properties),
+                    undirected: rel.undirected,
+                }
+            })
+            .collect();
+
+        Self {
+            nodes,
+            relationships,
+            between,
+        }
+    }
+
+    fn between(&self, source: usize, target: usize) -> &[usize] {
+        self.between
+            .get(&(source, target))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Backtracking search for embeddings of a pattern in a host graph.
+///
+/// If `exact` is set, labels, properties and relationships have to be equal
+/// instead of contained, and only bijections are considered, which turns
+/// the search into an isomorphism test.
+pub(crate) struct Matcher<'a> {
+    pattern: &'a Prepared,
+    host: &'a Prepared,
+    exact: bool,
+    order: Vec<usize>,
+    candidates: Vec<Vec<usize>>,
+    /// Stops the search after this many assignments of a pattern node.
+    max_iterations: Option<usize>,
+    iterations: Cell<usize>,
+    /// Whether the search was stopped by `max_iterations`.
+    exhausted: Cell<bool>,
+}
+
+impl<'a> Matcher<'a> {
+    /// Matches labels exactly if `exact` is set, as a subset otherwise.
+    pub(crate) fn new(pattern: &'a Prepared, host: &'a Prepared, exact: bool) -> Self {
+        let labels = if exact {
+            LabelMatch::Exact
+        } else {
+            LabelMatch::Subset
+        };
+        Self::with_label_match(pattern, host, exact, labels)
+    }
+
+    pub(crate) fn with_label_match(
+        pattern: &'a Prepared,
+        host: &'a Prepared,
+        exact: bool,
+        labels: LabelMatch,
+    ) -> Self {
+        let candidates = pattern
+            .nodes
+            .iter()
+            .map(|node| {
+                (0..host.nodes.len())
+                    .filter(|&candidate| {
+                        node_compatible(node, &host.nodes[candidate], exact, labels)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            pattern,
+            host,
+            exact,
+            order: search_order(pattern),
+            candidates,
+            max_iterations: None,
+            iterations: Cell::new(0),
+            exhausted: Cell::new(false),
+        }
+    }
+
+    pub(crate) fn count(&self) -> usize {
+        let mut count = 0;
+        self.for_each(|_| {
+            count += 1;
+            true
+        });
+        count
+    }
+
+    /// Calls `visit` with every embedding, given as the host node of each
+    /// pattern node, until `visit` returns `false`.
+    pub(crate) fn for_each(&self, mut visit: impl FnMut(&[usize]) -> bool) {
+        if self.exact
+            && (self.pattern.nodes.len() != self.host.nodes.len()
+                || self.pattern.relationships.len() != self.host.relationships.len())
+        {
+            return;
+        }
+
+        let mut mapping = vec![usize::MAX; self.pattern.nodes.len()];
+        let mut used = vec![false; self.host.nodes.len()];
+        self.extend(0, &mut mapping, &mut used, &mut visit);
+    }
+
+    fn extend(
+        &self,
+        depth: usize,
+        mapping: &mut [usize],
+        used: &mut [bool],
+        visit: &mut impl FnMut(&[usize]) -> bool,
+    ) -> bool {
+        if depth == self.order.len() {
+            return visit(mapping);
+        }
+
+        let node = self.order[depth];
+        for &candidate in &self.candidates[node] {
+            if used[candidate] {
+                continue;
+            }
+            if self.max_iterations == Some(self.iterations.get()) {
+                self.exhausted.set(true);
+                return false;
+            }
+            self.iterations.set(self.iterations.get() + 1);
+            mapping[node] = candidate;
+            if !self.consistent(node, depth, mapping) {
+                continue;
+            }
+            used[candidate] = true;
+            let proceed = self.extend(depth + 1, mapping, used, visit);
+            used[candidate] = false;
+            if !proceed {
+                return false;
+            }
+        }
+        mapping[node] = usize::MAX;
+
+        true
+    }
+
+    /// Checks the relationships between `node` and all previously mapped nodes.
+    fn consistent(&self, node: usize, depth: usize, mapping: &[usize]) -> bool {
+        self.order[..=depth].iter().all(|&other| {
+            self.relationships_compatible(node, other, mapping)
+                && (other == node || self.relationships_compatible(other, node, mapping))
+        })
+    }
+
+    fn relationships_compatible(&self, source: usize, target: usize, mapping: &[usize]) -> bool {
+        let pattern = self.pattern.between(source, target);
+        let host = self.host.between(mapping[source], mapping[target]);
+
+        if self.exact && pattern.len() != host.len() || pattern.len() > host.len() {
+            return false;
+        }
+
+        has_matching(pattern.len(), host.len(), &|p, h| {
+            relationship_compatible(
+                &self.pattern.relationships[pattern[p]],
+                &self.host.relationships[host[h]],
+                self.exact,
+            )
+        })
+    }
+}
+
+/// Orders pattern nodes such that every node is connected to as many
+/// previously ordered nodes as possible, which prunes the search early.
+fn search_order(pattern: &Prepared) -> Vec<usize> {
+    let node_count = pattern.nodes.len();
+    let mut connections = vec![0; node_count];
+    let mut ordered = vec![false; node_count];
+    let mut order = Vec::with_capacity(node_count);
+
+    while order.len() < node_count {
+        let next = (0..node_count)
+            .filter(|&node| !ordered[node])
+            .max_by_key(|&node| {
+                let degree = pattern.nodes[node].out_degree + pattern.nodes[node].in_degree;
+                (connections[node], degree, std::cmp::Reverse(node))
+            })
+            .unwrap();
+
+        ordered[next] = true;
+        order.push(next);
+        pattern.between.keys().for_each(|&(source, target)| {
+            if source == next {
+                connections[target] += 1;
+            }
+            if target == next {
+                connections[source] += 1;
+            }
+        });
+    }
+
+    order
+}
+
+fn node_compatible(
+    pattern: &NodeTokens,
+    host: &NodeTokens,
+    exact: bool,
+    labels: LabelMatch,
+) -> bool {
+    let labels = match labels {
+        LabelMatch::Exact => pattern.labels == host.labels,
+        LabelMatch::Subset => contains(&host.labels, &pattern.labels),
+    };
+    labels
+        && if exact {
+            pattern.properties == host.properties
+                && pattern.out_degree == host.out_degree
+                && pattern.in_degree == host.in_degree
+        } else {
+            contains(&host.properties, &pattern.properties)
+                && pattern.out_degree <= host.out_degree
+                && pattern.in_degree <= host.in_degree
+        }
+}
+
+fn relationship_compatible(
+    pattern: &RelationshipTokens,
+    host: &RelationshipTokens,
+    exact: bool,
+) -> bool {
+    pattern.rel_type == host.rel_type
+        && pattern.undirected == host.undirected
+        && if exact {
+            pattern.properties == host.properties
+        } else {
+            contains(&host.properties, &pattern.properties)
+        }
+}
+
+/// Checks that every token of the sorted `subset` occurs in the sorted `set`.
+fn contains(set: &[String], subset: &[String]) -> bool {
+    subset.iter().all(|token| set.binary_search(token).is_ok())
+}
+
+/// Checks whether every left vertex of a bipartite graph can be matched to
+/// a distinct right vertex, using augmenting paths.
+fn has_matching(left: usize, right: usize, compatible: &dyn Fn(usize, usize) -> bool) -> bool {
+    let mut matched = vec![None; right];
+    (0..left).all(|vertex| augment(vertex, &mut vec![false; right], &mut matched, compatible))
+}
+
+fn augment(
+    vertex: usize,
+    seen: &mut [bool],
+    matched: &mut [Option<usize>],
+    compatible: &dyn Fn(usize, usize) -> bool,
+) -> bool {
+    for candidate in 0..seen.len() {
+        if seen[candidate] || !compatible(vertex, candidate) {
+            continue;
+        }
+        seen[candidate] = true;
+        if matched[candidate].map_or(true, |other| augment(other, seen, matched, compatible)) {
+            matched[candidate] = Some(vertex);
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(all(feature = "gdl", test))]
+mod tests {
+    use super::*;
+    use crate::SimpleGraph;
+
+    use ::gdl::Graph as GdlGraph;
+
+    fn from_gdl(gdl: &str) -> GdlGraph {
+        gdl.parse::<GdlGraph>().unwrap()
+    }
+
+    fn complete_graph(n: usize) -> SimpleGraph {
+        let mut graph = SimpleGraph::new();
+        (0..n).for_each(|node| {
+            graph.add_node(node.to_string(), None::<&str>, None::<(&str, i64)>);
+        });
+        (0..n).for_each(|source| {
+            (0..n)
+                .filter(|&target| target != source)
+                .for_each(|target| {
+                    graph.add_edge(
+                        &source.to_string(),
+                        &target.to_string(),
+                        "",
+                        None::<(&str, i64)>,
+                    );
+                })
+        });
+        graph
+    }
+
+    #[test]
+    fn test_count_triangles_in_complete_graph() {
+        let cycle = from_gdl("(a)-->(b)-->(c)-->(a)");
+        let transitive = from_gdl("(a)-->(b)-->(c), (a)-->(c)");
+        let k4 = complete_graph(4);
+
+        // Every triangle of K4 carries a directed cycle in both orientations.
+        assert_eq!(count_pattern(&cycle, &k4), 8);
+        assert_eq!(count_pattern_with(&cycle, &k4, Embeddings::Raw), 24);
+        assert_eq!(count_pattern(&transitive, &k4), 24);
+
+        let k5 = complete_graph(5);
+        assert_eq!(count_pattern(&cycle, &k5), 20);
+    }
+
+    #[test]
+    fn test_count_labeled_pattern() {
+        let host = from_gdl(
+            "(a:Person {name: 'Alice'}), (b:Person), (c:City),
+             (a)-[:LIVES_IN {since: 2020}]->(c), (b)-[:LIVES_IN]->(c), (a)-[:KNOWS]->(b)",
+        );
+
+        let lives_in = from_gdl("(p:Person), (c:City), (p)-[:LIVES_IN]->(c)");
+        assert_eq!(count_pattern(&lives_in, &host), 2);
+
+        let since = from_gdl("(p:Person), (c:City), (p)-[:LIVES_IN {since: 2020}]->(c)");
+        assert_eq!(count_pattern(&since, &host), 1);
+
+        let works_at = from_gdl("(p:Person), (c:City), (p)-[:WORKS_AT]->(c)");
+        assert_eq!(count_pattern(&works_at, &host), 0);
+    }
+
+    #[test]
+    fn test_parallel_pattern_relationships_need_distinct_host_relationships() {
+        let pattern = from_gdl("(a)-[:R]->(b), (a)-[:R]->(b)");
+
+        assert_eq!(count_pattern(&pattern, &from_gdl("(a)-[:R]->(b)")), 0);
+        assert_eq!(
+            count_pattern(&pattern, &from_gdl("(a)-[:R]->(b), (a)-[:R]->(b)")),
+            1
+        );
+    }
+
+    #[test]
+    fn test_triangle_is_subgraph() {
+        let graph = from_gdl(
+            "(a:Person {name: 'Alice'}), (b:Person), (c:Person:Admin), (d:City),
+             (a)-[:KNOWS]->(b), (b)-[:KNOWS {since: 2020}]->(c), (c)-[:KNOWS]->(a),
+             (a)-[:LIVES_IN]->(d), (c)-[:LIVES_IN]->(d)",
+        );
+
+        let triangle =
+            from_gdl("(x:Person)-[:KNOWS]->(y:Person)-[:KNOWS]->(z:Admin)-[:KNOWS]->(x)");
+        assert!(is_subgraph_of(&triangle, &graph));
+
+        let with_since = from_gdl("(x)-[:KNOWS {since: 2020}]->(y)-[:KNOWS]->(z)-[:KNOWS]->(x)");
+        assert!(is_subgraph_of(&with_since, &graph));
+
+        let since_from_admin =
+            from_gdl("(x:Admin)-[:KNOWS {since: 2020}]->(y)-[:KNOWS]->(z)-[:KNOWS]->(x)");
+        assert!(!is_subgraph_of(&since_from_admin, &graph));
+
+        let wrong_property =
+            from_gdl("(x {name: 'Bob'})-[:KNOWS]->(y)-[:KNOWS]->(z)-[:KNOWS]->(x)");
+        assert!(!is_subgraph_of(&wrong_property, &graph));
+
+        let city_triangle = from_gdl("(x)-[:LIVES_IN]->(y)-[:LIVES_IN]->(z)-[:LIVES_IN]->(x)");
+        assert!(!is_subgraph_of(&city_triangle, &graph));
+    }
+
+    #[test]
+    fn test_exact_isomorphism() {
+        let g1 = from_gdl("(a:A {v: 1}), (b:B), (a)-[:R {w: 1}]->(b), (b)-[:R]->(b)");
+        let g2 = from_gdl("(x:B), (y:A {v: 1}), (y)-[:R {w: 1}]->(x), (x)-[:R]->(x)");
+        let g3 = from_gdl("(x:B), (y:A {v: 1}), (y)-[:R {w: 2}]->(x), (x)-[:R]->(x)");
+
+        assert!(assert_graph_iso_exact(&g1, &g2));
+        assert!(!assert_graph_iso_exact(&g1, &g3));
+        assert!(!assert_graph_iso_exact(&g1, &from_gdl("(a:A {v: 1})")));
+    }
+
+    #[test]
+    fn test_exact_isomorphism_separates_color_equivalent_graphs() {
+        let six_cycle = from_gdl("(a)-->(b)-->(c)-->(d)-->(e)-->(f)-->(a)");
+        let two_triangles = from_gdl("(a)-->(b)-->(c)-->(a), (d)-->(e)-->(f)-->(d)");
+        assert!(crate::equals(&six_cycle, &two_triangles));
+        assert!(!assert_graph_iso_exact(&six_cycle, &two_triangles));
+
+        let rotated = from_gdl("(d)-->(e)-->(f)-->(a)-->(b)-->(c)-->(d)");
+        assert!(assert_graph_iso_exact(&six_cycle, &rotated));
+    }
+
+    #[test]
+    fn test_graph_isomorphism_with_automorphisms() {
+        let g1 = from_gdl("(a)-->(b)-->(c)-->(a), (c)-[:R]->(d:D)");
+        let g2 = from_gdl("(x)-->(y)-->(z)-->(x), (y)-[:R]->(w:D)");
+
+        let mapping = graph_isomorphism(&g1, &g2).unwrap();
+        assert_eq!(mapping.len(), 4);
+        assert_eq!(
+            [mapping["a"], mapping["b"], mapping["c"], mapping["d"]],
+            ["z", "x", "y", "w"]
+        );
+
+        let cycle = from_gdl("(a)-->(b)-->(c)-->(a)");
+        let mapping = graph_isomorphism(&cycle, &cycle).unwrap();
+        Graph::nodes(&cycle).for_each(|node| {
+            let image = mapping[node];
+            let successor = |node| {
+                cycle
+                    .outgoing_relationships(node)
+                    .map(|((target, _), _)| target)
+                    .next()
+                    .unwrap()
+            };
+            assert_eq!(mapping[successor(node)], successor(image));
+        });
+
+        assert!(graph_isomorphism(&cycle, &g1).is_none());
+    }
+
+    #[test]
+    fn test_exact_isomorphism_within_budget() {
+        let g1 = from_gdl("(a:A)-->(b)-->(c)-->(a)");
+        let g2 = from_gdl("(x)-->(y:A)-->(z)-->(x)");
+
+        let unlimited = CompareOptions::new();
+        assert_eq!(
+            assert_graph_iso_exact_within(&g1, &g2, &unlimited),
+            Ok(true)
+        );
+        let enough = CompareOptions::new().max_nodes(3).max_iterations(100);
+        assert_eq!(assert_graph_iso_exact_within(&g1, &g2, &enough), Ok(true));
+
+        assert_eq!(
+            assert_graph_iso_exact_within(&g1, &g2, &CompareOptions::new().max_nodes(0)),
+            Err(BudgetExceeded::Nodes {
+                limit: 0,
+                actual: 3
+            })
+        );
+        let error =
+            assert_graph_iso_exact_within(&g1, &g2, &CompareOptions::new().max_iterations(0))
+                .unwrap_err();
+        assert_eq!(error, BudgetExceeded::Iterations { limit: 0 });
+        assert_eq!(
+            error.to_string(),
+            "search exceeded the budget of 0 iterations"
+        );
+
+        // Graphs that cannot be isomorphic are rejected without a search.
+        assert_eq!(
+            assert_graph_iso_exact_within(
+                &g1,
+                &from_gdl("(a)-->(b)"),
+                &CompareOptions::new().max_iterations(0)
+            ),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn test_exact_isomorphism_within_budget_applies_options() {
+        let g1 = from_gdl("(a:A)-[:R {w: 1}]->(b)");
+        let g2 = from_gdl("(a:A)-[:R {w: 2}]->(b)");
+
+        assert_eq!(
+            assert_graph_iso_exact_within(&g1, &g2, &CompareOptions::new()),
+            Ok(false)
+        );
+        assert_eq!(
+            assert_graph_iso_exact_within(
+                &g1,
+                &g2,
+                &CompareOptions::new().ignore_property_keys(vec!["w"])
+            ),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_label_match() {
+        let a = from_gdl("(n:A)");
+        let ab = from_gdl("(n:A:B)");
+        let b = from_gdl("(n:B)");
+        let exact = CompareOptions::new();
+        let subset = CompareOptions::new().label_match(LabelMatch::Subset);
+        let iso = |left, right, options| assert_graph_iso_exact_within(left, right, options);
+
+        // canonical forms distinguish overlapping label sets
+        assert!(!crate::equals(&a, &ab));
+        assert!(!crate::equals(&ab, &b));
+
+        assert_eq!(exact.label_match, LabelMatch::Exact);
+        assert_eq!(iso(&a, &a, &exact), Ok(true));
+        assert_eq!(iso(&a, &ab, &exact), Ok(false));
+        assert_eq!(iso(&b, &ab, &exact), Ok(false));
+        assert!(!is_subgraph_of_with(&a, &ab, &exact));
+        assert!(is_subgraph_of_with(&ab, &ab, &exact));
+
+        assert_eq!(iso(&a, &ab, &subset), Ok(true));
+        assert_eq!(iso(&b, &ab, &subset), Ok(true));
+        assert_eq!(iso(&ab, &a, &subset), Ok(false));
+        assert_eq!(iso(&a, &b, &subset), Ok(false));
+        assert!(is_subgraph_of_with(&a, &ab, &subset));
+        assert!(is_subgraph_of_with(&b, &ab, &subset));
+        assert!(!is_subgraph_of_with(&ab, &b, &subset));
+        assert!(!is_subgraph_of_with(&a, &b, &subset));
+    }
+
+    #[test]
+    fn test_compare() {
+        let six_cycle = from_gdl("(a)-->(b)-->(c)-->(d)-->(e)-->(f)-->(a)");
+        let two_triangles = from_gdl("(a)-->(b)-->(c)-->(a), (d)-->(e)-->(f)-->(d)");
+        let triangle = from_gdl("(a:A)-->(b)-->(c)-->(a)");
+        let rotated = from_gdl("(x)-->(y:A)-->(z)-->(x)");
+        let options = CompareOptions::new();
+
+        assert_eq!(
+            compare(&triangle, &rotated, &options),
+            IsoResult::Isomorphic
+        );
+        assert_eq!(
+            compare(&triangle, &from_gdl("(a:A)-->(b)-->(c)"), &options),
+            IsoResult::NotIsomorphic
+        );
+        // equal canonical forms, but no bijection
+        assert!(crate::equals(&six_cycle, &two_triangles));
+        assert_eq!(
+            compare(&six_cycle, &two_triangles, &options),
+            IsoResult::NotIsomorphic
+        );
+
+        let small = CompareOptions::new().max_iterations(0);
+        assert_eq!(compare(&triangle, &rotated, &small), IsoResult::Undecided);
+        assert_eq!(
+            compare(
+                &six_cycle,
+                &two_triangles,
+                &CompareOptions::new().max_nodes(5)
+            ),
+            IsoResult::Undecided
+        );
+        // differing canonical forms are decided without searching
+        assert_eq!(
+            compare(&triangle, &six_cycle, &small),
+            IsoResult::NotIsomorphic
+        );
+
+        assert!(IsoResult::Isomorphic.is_isomorphic());
+        assert!(!IsoResult::NotIsomorphic.is_isomorphic());
+        assert!(!IsoResult::Undecided.is_isomorphic());
+    }
+
+    #[test]
+    fn test_automorphism_count() {
+        assert_eq!(automorphism_count(&from_gdl("(a)-->(b)-->(c)-->(d)")), 1);
+        assert_eq!(automorphism_count(&from_gdl("(a:A), (b:B), (c:C)")), 1);
+        assert_eq!(
+            automorphism_count(&from_gdl("(a:A)-->(b:B)-->(c:C)-->(a)")),
+            1
+        );
+        assert_eq!(automorphism_count(&from_gdl("(a), (b), (c)")), 6);
+        assert_eq!(automorphism_count(&complete_graph(4)), 24);
+        assert_eq!(automorphism_count(&SimpleGraph::new()), 1);
+
+        let mut triangle = SimpleGraph::new_undirected();
+        ["a", "b", "c"].iter().for_each(|node| {
+            triangle.add_node(*node, None::<&str>, None::<(&str, i64)>);
+        });
+        [("a", "b"), ("b", "c"), ("c", "a")]
+            .iter()
+            .for_each(|(source, target)| {
+                triangle.add_edge(*source, *target, "", None::<(&str, i64)>);
+            });
+        assert_eq!(automorphism_count(&triangle), 6);
+        assert_eq!(automorphism_count(&from_gdl("(a)-->(b)-->(c)-->(a)")), 3);
+    }
+
+    #[test]
+    fn test_undirected_relationships_match_both_ways() {
+        let mut path = SimpleGraph::new_undirected();
+        ["a", "b", "c"].iter().for_each(|node| {
+            path.add_node(*node, None::<&str>, None::<(&str, i64)>);
+        });
+        path.add_edge("a", "b", "R", None::<(&str, i64)>);
+        path.add_edge("c", "b", "R", None::<(&str, i64)>);
+
+        let mut reversed = SimpleGraph::new_undirected();
+        ["x", "y", "z"].iter().for_each(|node| {
+            reversed.add_node(*node, None::<&str>, None::<(&str, i64)>);
+        });
+        reversed.add_edge("y", "x", "R", None::<(&str, i64)>);
+        reversed.add_edge("y", "z", "R", None::<(&str, i64)>);
+
+        assert!(assert_graph_iso_exact(&path, &reversed));
+        assert_eq!(automorphism_count(&path), 2);
+        assert!(!assert_graph_iso_exact(
+            &path,
+            &from_gdl("(x)-[:R]->(y), (z)-[:R]->(y)")
+        ));
+    }
+}
@@ -0,0 +1,560 @@
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    string::String,
+    sync::Arc,
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::Value;
+
+/// Configures how graphs are canonicalized and compared.
+///
+/// The default options compare graphs exactly as `canonicalize` does.
+#[derive(Clone, Debug, Default)]
+pub struct CompareOptions {
+    /// Merges parallel relationships with the same type into a single
+    /// relationship carrying the union of their properties.
+    pub merge_parallel_edge_properties: bool,
+    /// Treats parallel relationships as a set: of all relationships with the
+    /// same source, target, type and properties, only one is kept. Applied
+    /// after all other options. By default, parallel relationships are
+    /// compared as a multiset.
+    pub dedup_parallel_edges: bool,
+    /// Resolves conflicting property values while merging parallel relationships.
+    pub parallel_edge_conflict: ParallelEdgeConflict,
+    /// Decides which value wins when a node or relationship carries the same
+    /// property key more than once. By default, all distinct values are kept.
+    pub on_duplicate_property_key: Option<DuplicatePropertyKey>,
+    /// Rounds float node properties, also within lists, to the nearest
+    /// multiple of the epsilon before comparing them. The setter panics
+    /// unless the epsilon is positive and finite.
+    /// Relies on `Graph::property_value`.
+    pub node_float_epsilon: Option<f64>,
+    /// Like `node_float_epsilon`, but rounds the float properties of both
+    /// nodes and relationships. The setter panics unless the epsilon is
+    /// positive and finite. Relies on `Graph::property_value`.
+    pub float_epsilon: Option<f64>,
+    /// Renders float properties of nodes and relationships with this many
+    /// decimal places, e.g., both `0.1 + 0.2` and `0.3` as `0.30` for a
+    /// precision of 2. Integers are left untouched. Applied after all other
+    /// options that work on numbers. Relies on `Graph::property_value`,
+    /// adapters that do not implement it keep the `Display` of their values.
+    pub float_precision: Option<usize>,
+    /// Treats properties with a `null` value as if they were absent.
+    /// Relies on `Graph::property_value`.
+    pub null_as_absent: bool,
+    /// Reads the relationship type from the named relationship property
+    /// instead of `Graph::outgoing_relationships`. The property itself is
+    /// not rendered. Relationships without the property keep their type.
+    pub relationship_type_property: Option<String>,
+    /// Rewrites every node and relationship property before comparison.
+    /// Relies on `Graph::property_value`.
+    pub property_transform: Option<Transform>,
+    /// Drops all node and relationship properties with one of these keys
+    /// before comparison, e.g., timestamps or internal ids.
+    pub ignored_property_keys: BTreeSet<String>,
+    /// Like `ignored_property_keys`, but only drops relationship properties,
+    /// node properties with these keys are still compared.
+    pub ignored_relationship_property_keys: BTreeSet<String>,
+    /// Drops all relationship properties before comparison, while node
+    /// labels, node properties and relationship types are still compared.
+    pub ignore_relationship_properties: bool,
+    /// Drops all relationships with one of these types before comparison.
+    /// Nodes are kept, even if they become isolated.
+    pub ignored_relationship_types: BTreeSet<String>,
+    /// Compares list properties as unordered multisets by sorting their
+    /// elements. Relies on `Graph::property_value`.
+    pub sort_lists: bool,
+    /// Treats strings that parse as a number, e.g., `"42"` or `"0.50"`, as
+    /// that number. Since strings are rendered without quotes, this mostly
+    /// matters for numbers with several spellings and for options that
+    /// compare values. Applied before `normalize_numbers`.
+    /// Relies on `Graph::property_value`.
+    pub coerce_numeric_strings: bool,
+    /// Represents every number by a single value: `-0.0` becomes `0` and
+    /// floats with an integral value become integers, as long as they are
+    /// exactly representable. Integral floats already render like integers,
+    /// so this only matters for `-0.0` and for options that use the float
+    /// itself, e.g., `float_precision`. Relies on `Graph::property_value`.
+    pub normalize_numbers: bool,
+    /// Replaces node labels by a property, e.g., `:Active` by `active: true`.
+    /// Relies on `Graph::property_value`.
+    pub labels_as_properties: BTreeMap<String, (String, Value)>,
+    /// Replaces node properties with a given value by a label, e.g.,
+    /// `active: true` by `:Active`. Relies on `Graph::property_value`.
+    pub properties_as_labels: Vec<((String, Value), String)>,
+    /// Drops all relationships whose numeric property `key` is below the
+    /// threshold. Relationships without a numeric value for `key` are kept,
+    /// nodes are kept even if they become isolated.
+    /// Relies on `Graph::property_value`.
+    pub min_edge_weight: Option<(String, f64)>,
+    /// Replaces parallel relationships with the same type and the same
+    /// properties apart from the numeric property `key` by a single
+    /// relationship whose `key` is the sum of their values, e.g.,
+    /// `-[{w: 1}]->` and `-[{w: 2}]->` by `-[{w: 3}]->`. Relationships
+    /// without a numeric value for `key` are kept as they are.
+    /// Relies on `Graph::property_value`.
+    pub sum_parallel_edge_weights: Option<String>,
+    /// Merges every pair of reciprocal relationships `(a)-[:T]->(b)` and
+    /// `(b)-[:T]->(a)` with equal properties into a single undirected
+    /// relationship. Panics if reciprocal relationships disagree on their
+    /// properties.
+    pub merge_reciprocal_edges: bool,
+    /// Ignores the direction of all relationships. Parallel relationships
+    /// and self-loops keep their multiplicity, a self-loop is listed twice
+    /// among the relationships of its node.
+    pub treat_as_undirected: bool,
+    /// Ignores the direction of relationships with one of these types, like
+    /// `treat_as_undirected` does for all relationships. Relationships of
+    /// other types keep their direction. Types are matched exactly, before
+    /// `case_insensitive_rel_types` is applied.
+    pub symmetric_rel_types: BTreeSet<String>,
+    /// Compares node labels ignoring their case, e.g., `:Person` equals
+    /// `:PERSON`. Labels that only differ in case are treated as one label.
+    /// Applied after `labels_as_properties` and `properties_as_labels`.
+    pub case_insensitive_labels: bool,
+    /// Compares the labels of a node as a multiset instead of a set, e.g.,
+    /// `:A:A:B` differs from `:A:B`. Labels that become equal through
+    /// `case_insensitive_labels` are counted separately, too.
+    pub labels_as_multiset: bool,
+    /// Compares relationship types ignoring their case. Applied after
+    /// `ignored_relationship_types`, which still matches types exactly.
+    pub case_insensitive_rel_types: bool,
+    /// Compares the ids of relationships, see `Graph::relationship_id`, so
+    /// two relationships only correspond if they have the same id or both
+    /// have none. Ids are rendered after the type, as in `[:T #id ]`.
+    ///
+    /// Normally, ids do not affect isomorphism: like node ids, they merely
+    /// name relationships and differ between otherwise equal graphs. Only
+    /// set this if both graphs draw their ids from the same source. Ids are
+    /// compared verbatim and kept by `topology_only`, relationships with
+    /// different ids are never merged or deduplicated.
+    pub use_relationship_ids: bool,
+    /// Compares only how nodes are connected: node labels and all properties
+    /// are dropped after all other options are applied. Relationship types
+    /// are still compared.
+    pub topology_only: bool,
+    /// Aborts the exact comparison, see `assert_graph_iso_exact_within`, if a
+    /// graph has more nodes than this.
+    pub max_nodes: Option<usize>,
+    /// Aborts the exact comparison, see `assert_graph_iso_exact_within`,
+    /// after trying this many assignments of a node to a candidate node.
+    pub max_iterations: Option<usize>,
+    /// How the node search of `assert_graph_iso_exact_within` and
+    /// `is_subgraph_of_with` matches labels. Canonical forms always compare
+    /// label sets exactly, so `equals` ignores this.
+    pub label_match: LabelMatch,
+    /// Renders property values instead of their `Display` implementation.
+    /// Applied after all other options. Relies on `Graph::property_value`.
+    pub property_renderer: Option<Arc<dyn PropertyRenderer>>,
+    /// Orders the rows of `canonicalize_with` by a caller-defined key instead
+    /// of by the rows themselves, see `CompareOptions::node_order_key`.
+    pub node_order_key: Option<NodeOrderKey>,
+}
+
+impl CompareOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates options that only compare the topology, see `topology_only`.
+    pub fn topology_only() -> Self {
+        Self {
+            topology_only: true,
+            ..Self::default()
+        }
+    }
+
+    /// Whether isomorphic graphs have the same number of nodes and
+    /// relationships under these options, i.e., no relationships are
+    /// dropped or merged.
+    pub(crate) fn preserves_counts(&self) -> bool {
+        !self.merge_parallel_edge_properties
+            && !self.dedup_parallel_edges
+            && !self.merge_reciprocal_edges
+            && self.ignored_relationship_types.is_empty()
+            && self.min_edge_weight.is_none()
+            && self.sum_parallel_edge_weights.is_none()
+    }
+
+    pub fn merge_parallel_edge_properties(mut self, merge: bool) -> Self {
+        self.merge_parallel_edge_properties = merge;
+        self
+    }
+
+    pub fn dedup_parallel_edges(mut self, dedup: bool) -> Self {
+        self.dedup_parallel_edges = dedup;
+        self
+    }
+
+    pub fn parallel_edge_conflict(mut self, policy: ParallelEdgeConflict) -> Self {
+        self.parallel_edge_conflict = policy;
+        self
+    }
+
+    pub fn on_duplicate_property_key(mut self, policy: DuplicatePropertyKey) -> Self {
+        self.on_duplicate_property_key = Some(policy);
+        self
+    }
+
+    pub fn node_float_epsilon(mut self, epsilon: f64) -> Self {
+        check_epsilon(epsilon);
+        self.node_float_epsilon = Some(epsilon);
+        self
+    }
+
+    pub fn float_epsilon(mut self, epsilon: f64) -> Self {
+        check_epsilon(epsilon);
+        self.float_epsilon = Some(epsilon);
+        self
+    }
+
+    pub fn float_precision(mut self, precision: usize) -> Self {
+        self.float_precision = Some(precision);
+        self
+    }
+
+    pub fn null_as_absent(mut self, null_as_absent: bool) -> Self {
+        self.null_as_absent = null_as_absent;
+        self
+    }
+
+    pub fn relationship_type_from_property(mut self, key: impl Into<String>) -> Self {
+        self.relationship_type_property = Some(key.into());
+        self
+    }
+
+    pub fn property_transform_all(mut self, transform: Transform) -> Self {
+        self.property_transform = Some(transform);
+        self
+    }
+
+    pub fn coerce_numeric_strings(mut self, coerce: bool) -> Self {
+        self.coerce_numeric_strings = coerce;
+        self
+    }
+
+    pub fn normalize_numbers(mut self, normalize_numbers: bool) -> Self {
+        self.normalize_numbers = normalize_numbers;
+        self
+    }
+
+    pub fn sort_lists(mut self, sort_lists: bool) -> Self {
+        self.sort_lists = sort_lists;
+        self
+    }
+
+    pub fn label_as_property<L, K, V>(
+        mut self,
+        mapping: impl IntoIterator<Item = (L, (K, V))>,
+    ) -> Self
+    where
+        L: Into<String>,
+        K: Into<String>,
+        V: Into<Value>,
+    {
+        self.labels_as_properties.extend(
+            mapping
+                .into_iter()
+                .map(|(label, (key, value))| (label.into(), (key.into(), value.into()))),
+        );
+        self
+    }
+
+    pub fn property_as_label<K, V, L>(
+        mut self,
+        mapping: impl IntoIterator<Item = ((K, V), L)>,
+    ) -> Self
+    where
+        K: Into<String>,
+        V: Into<Value>,
+        L: Into<String>,
+    {
+        self.properties_as_labels.extend(
+            mapping
+                .into_iter()
+                .map(|((key, value), label)| ((key.into(), value.into()), label.into())),
+        );
+        self
+    }
+
+    pub fn min_edge_weight(mut self, key: impl Into<String>, threshold: f64) -> Self {
+        self.min_edge_weight = Some((key.into(), threshold));
+        self
+    }
+
+    pub fn sum_parallel_edge_weights(mut self, key: impl Into<String>) -> Self {
+        self.sum_parallel_edge_weights = Some(key.into());
+        self
+    }
+
+    pub fn merge_reciprocal_edges(mut self, merge: bool) -> Self {
+        self.merge_reciprocal_edges = merge;
+        self
+    }
+
+    pub fn treat_as_undirected(mut self, undirected: bool) -> Self {
+        self.treat_as_undirected = undirected;
+        self
+    }
+
+    pub fn symmetric_rel_types<T>(mut self, rel_types: impl IntoIterator<Item = T>) -> Self
+    where
+        T: Into<String>,
+    {
+        self.symmetric_rel_types
+            .extend(rel_types.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn case_insensitive_labels(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive_labels = case_insensitive;
+        self
+    }
+
+    pub fn labels_as_multiset(mut self, multiset: bool) -> Self {
+        self.labels_as_multiset = multiset;
+        self
+    }
+
+    pub fn case_insensitive_rel_types(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive_rel_types = case_insensitive;
+        self
+    }
+
+    pub fn use_relationship_ids(mut self, use_ids: bool) -> Self {
+        self.use_relationship_ids = use_ids;
+        self
+    }
+
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = Some(max_iterations);
+        self
+    }
+
+    pub fn label_match(mut self, label_match: LabelMatch) -> Self {
+        self.label_match = label_match;
+        self
+    }
+
+    pub fn property_renderer(mut self, renderer: Box<dyn PropertyRenderer>) -> Self {
+        self.property_renderer = Some(Arc::from(renderer));
+        self
+    }
+
+    /// Orders the rows of `canonicalize_with` by `key`, which is called with
+    /// the `Debug` rendering of the node id, as in `canonicalize_with_ids`,
+    /// and with the canonical row of the node. Numbers are ordered
+    /// numerically, rows with the same key are ordered by the row.
+    ///
+    /// This is a display-only convenience, e.g., for golden files in a
+    /// domain specific order. The result is no longer canonical: isomorphic
+    /// graphs only render identically if `key` orders their nodes
+    /// consistently, so only compare graphs canonicalized with the same key.
+    ///
+    /// ```
+    /// use ::gdl::Graph as GdlGraph;
+    /// use assert_graph_iso::*;
+    ///
+    /// let g = "(a:Last), (b:First), (a)-->(b)".parse::<GdlGraph>().unwrap();
+    /// let options = CompareOptions::new().node_order_key(|id, _| Value::from(id));
+    ///
+    /// assert!(canonicalize_with(&g, &options).starts_with("(:Last )"));
+    /// ```
+    pub fn node_order_key(
+        mut self,
+        key: impl Fn(&str, &str) -> Value + Send + Sync + 'static,
+    ) -> Self {
+        self.node_order_key = Some(NodeOrderKey(Arc::new(key)));
+        self
+    }
+
+    pub fn ignore_property_keys<K>(mut self, keys: impl IntoIterator<Item = K>) -> Self
+    where
+        K: Into<String>,
+    {
+        self.ignored_property_keys
+            .extend(keys.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn ignore_relationship_property_keys<K>(mut self, keys: impl IntoIterator<Item = K>) -> Self
+    where
+        K: Into<String>,
+    {
+        self.ignored_relationship_property_keys
+            .extend(keys.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn ignore_relationship_properties(mut self, ignore: bool) -> Self {
+        self.ignore_relationship_properties = ignore;
+        self
+    }
+
+    pub fn ignore_relationship_types<T>(mut self, rel_types: impl IntoIterator<Item = T>) -> Self
+    where
+        T: Into<String>,
+    {
+        self.ignored_relationship_types
+            .extend(rel_types.into_iter().map(Into::into));
+        self
+    }
+}
+
+/// Panics unless `epsilon` is positive and finite, since rounding to
+/// multiples of zero, a negative or a NaN epsilon turns floats into NaN.
+fn check_epsilon(epsilon: f64) {
+    assert!(
+        epsilon > 0.0 && epsilon.is_finite(),
+        "Float epsilon must be positive and finite, got {}",
+        epsilon
+    );
+}
+
+/// How a node search matches the labels of a node to those of a candidate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum LabelMatch {
+    /// The label sets have to be identical, as in canonical forms.
+    #[default]
+    Exact,
+    /// The labels of the left or pattern node have to be a subset of the
+    /// labels of the candidate, e.g., `:A` matches `:A:B`, but not `:B`.
+    Subset,
+}
+
+/// What to do when merged parallel relationships disagree on a property value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ParallelEdgeConflict {
+    /// Panic with a message naming the conflicting property.
+    #[default]
+    Error,
+    /// Keep all distinct values as a sorted list.
+    List,
+    /// Keep the smallest value.
+    Min,
+    /// Keep the largest value.
+    Max,
+}
+
+/// What to do when a single node or relationship yields the same property key twice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicatePropertyKey {
+    /// Keep the value that was yielded first.
+    First,
+    /// Keep the value that was yielded last.
+    Last,
+    /// Panic with a message naming the duplicated key.
+    Error,
+}
+
+type ValueTransform = Arc<dyn Fn(Value) -> Value + Send + Sync>;
+type KeyTransform = Arc<dyn Fn(&str) -> String + Send + Sync>;
+type OrderKey = Arc<dyn Fn(&str, &str) -> Value + Send + Sync>;
+
+/// A rewrite applied uniformly to the properties of all nodes and relationships.
+///
+/// Rewriting values is how custom equivalences are expressed, e.g., case
+/// insensitive strings or domain-specific units. Canonicalization renders
+/// and sorts every property, so two values are treated as equal exactly if
+/// they are rewritten to values that render the same. For the same reason,
+/// there is no comparator hook: the canonical form needs a single
+/// representative for every group of equal values, which a comparator does
+/// not provide unless it is consistent with such a rewrite anyway.
+///
+/// ```
+/// use assert_graph_iso::{Transform, Value};
+///
+/// let transform = Transform::new()
+///     .values(|value| match value {
+///         Value::String(s) => Value::String(s.to_lowercase()),
+///         value => value,
+///     })
+///     .keys(|key| key.to_lowercase());
+/// ```
+#[derive(Clone, Default)]
+pub struct Transform {
+    pub(crate) values: Option<ValueTransform>,
+    pub(crate) keys: Option<KeyTransform>,
+}
+
+impl Transform {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn values(mut self, f: impl Fn(Value) -> Value + Send + Sync + 'static) -> Self {
+        self.values = Some(Arc::new(f));
+        self
+    }
+
+    /// Also rewrites property keys. Keys that become equal are treated as
+    /// duplicate keys, see `CompareOptions::on_duplicate_property_key`.
+    pub fn keys(mut self, f: impl Fn(&str) -> String + Send + Sync + 'static) -> Self {
+        self.keys = Some(Arc::new(f));
+        self
+    }
+}
+
+impl fmt::Debug for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transform")
+            .field("values", &self.values.is_some())
+            .field("keys", &self.keys.is_some())
+            .finish()
+    }
+}
+
+/// The key set by `CompareOptions::node_order_key`.
+#[derive(Clone)]
+pub struct NodeOrderKey(pub(crate) OrderKey);
+
+impl fmt::Debug for NodeOrderKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("NodeOrderKey")
+    }
+}
+
+/// Whether a property belongs to a node or to a relationship.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PropertyOwner {
+    Node,
+    Relationship,
+}
+
+/// Controls how a property value is rendered in the canonical form.
+///
+/// The canonical token of a property is `key: rendered`.
+///
+/// ```
+/// use assert_graph_iso::{CompareOptions, PropertyOwner, PropertyRenderer, Value};
+///
+/// struct Quoted;
+///
+/// impl PropertyRenderer for Quoted {
+///     fn render(&self, _owner: PropertyOwner, _key: &str, value: &Value) -> String {
+///         match value {
+///             Value::String(s) => format!("{:?}", s),
+///             value => value.to_string(),
+///         }
+///     }
+/// }
+///
+/// let options = CompareOptions::new().property_renderer(Box::new(Quoted));
+/// ```
+pub trait PropertyRenderer: Send + Sync {
+    fn render(&self, owner: PropertyOwner, key: &str, value: &Value) -> String;
+}
+
+impl fmt::Debug for dyn PropertyRenderer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PropertyRenderer")
+    }
+}
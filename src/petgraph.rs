@@ -0,0 +1,609 @@
+use std::cell::OnceCell;
+
+use ::petgraph::{csr::Csr, graph::NodeIndex, visit::EdgeRef, Direction};
+
+use crate::{
+    graph::{Graph, LabelIterator, NodesIterator, PropertyIterator, RelationshipIterator},
+    Value,
+};
+
+type PropertyList = Vec<(String, Value)>;
+type Labels<'g, N> = Box<dyn Fn(&N) -> Vec<String> + 'g>;
+type Properties<'g, W> = Box<dyn Fn(&W) -> PropertyList + 'g>;
+type RelType<'g, E> = Box<dyn Fn(&E) -> String + 'g>;
+
+/// Provides the labels and properties of a node from its petgraph weight.
+pub trait NodeWeight {
+    fn labels(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn properties(&self) -> PropertyList {
+        Vec::new()
+    }
+}
+
+/// Provides the type and properties of a relationship from its petgraph weight.
+pub trait EdgeWeight {
+    fn rel_type(&self) -> String {
+        String::new()
+    }
+
+    fn properties(&self) -> PropertyList {
+        Vec::new()
+    }
+}
+
+impl NodeWeight for () {}
+
+impl EdgeWeight for () {}
+
+/// A string node weight is the single label of the node.
+impl NodeWeight for &str {
+    fn labels(&self) -> Vec<String> {
+        vec![self.to_string()]
+    }
+}
+
+impl NodeWeight for String {
+    fn labels(&self) -> Vec<String> {
+        vec![self.clone()]
+    }
+}
+
+/// A string edge weight is the type of the relationship.
+impl EdgeWeight for &str {
+    fn rel_type(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl EdgeWeight for String {
+    fn rel_type(&self) -> String {
+        self.clone()
+    }
+}
+
+struct NodeData {
+    labels: Vec<String>,
+    properties: PropertyList,
+}
+
+struct EdgeData {
+    rel_type: String,
+    properties: PropertyList,
+}
+
+/// Converts weights on first access and keeps the result, since the
+/// `Graph` trait hands out references to labels and properties.
+struct WeightCache {
+    nodes: Vec<OnceCell<NodeData>>,
+    edges: Vec<OnceCell<EdgeData>>,
+}
+
+impl WeightCache {
+    fn new(node_count: usize, edge_count: usize) -> Self {
+        Self {
+            nodes: (0..node_count).map(|_| OnceCell::new()).collect(),
+            edges: (0..edge_count).map(|_| OnceCell::new()).collect(),
+        }
+    }
+
+    fn node(&self, index: usize, weight: &impl NodeWeight) -> &NodeData {
+        self.nodes[index].get_or_init(|| NodeData {
+            labels: weight.labels(),
+            properties: weight.properties(),
+        })
+    }
+
+    fn edge(&self, index: usize, weight: &impl EdgeWeight) -> &EdgeData {
+        self.edges[index].get_or_init(|| EdgeData {
+            rel_type: weight.rel_type(),
+            properties: weight.properties(),
+        })
+    }
+
+    fn node_with(&self, index: usize, init: impl FnOnce() -> NodeData) -> &NodeData {
+        self.nodes[index].get_or_init(init)
+    }
+
+    fn edge_with(&self, index: usize, init: impl FnOnce() -> EdgeData) -> &EdgeData {
+        self.edges[index].get_or_init(init)
+    }
+}
+
+fn into_properties<P, K, V>(properties: P) -> PropertyList
+where
+    P: IntoIterator<Item = (K, V)>,
+    K: Into<String>,
+    V: Into<Value>,
+{
+    properties
+        .into_iter()
+        .map(|(key, value)| (key.into(), value.into()))
+        .collect()
+}
+
+fn properties(properties: &[(String, Value)]) -> PropertyIterator<'_, &str, &Value> {
+    Box::new(properties.iter().map(|(key, value)| (key.as_str(), value)))
+}
+
+/// Adapts a directed `petgraph::Graph` to the `Graph` trait.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::{equals, petgraph::PetGraph};
+///
+/// let mut graph = petgraph::Graph::<&str, &str>::new();
+/// let a = graph.add_node("Person");
+/// let b = graph.add_node("City");
+/// graph.add_edge(a, b, "LIVES_IN");
+///
+/// let expected = "(a:Person), (b:City), (a)-[:LIVES_IN]->(b)".parse::<GdlGraph>().unwrap();
+///
+/// assert!(equals(&PetGraph::new(&graph), &expected));
+/// ```
+///
+/// Weights that do not implement `NodeWeight` or `EdgeWeight`, e.g., plain
+/// numbers or types of other crates, are read by closures instead:
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::{equals, petgraph::PetGraph};
+///
+/// let mut graph = petgraph::Graph::<u32, f64>::new();
+/// let a = graph.add_node(1);
+/// let b = graph.add_node(2);
+/// graph.add_edge(a, b, 0.5);
+///
+/// let adapter = PetGraph::with_extractors(&graph)
+///     .properties(|&id| [("id", i64::from(id))])
+///     .rel_type(|_| "REL")
+///     .relationship_properties(|&weight| [("weight", weight)]);
+///
+/// let expected = "(a { id: 1 }), (b { id: 2 }), (a)-[:REL { weight: 0.5 }]->(b)"
+///     .parse::<GdlGraph>()
+///     .unwrap();
+///
+/// assert!(equals(&adapter, &expected));
+/// ```
+pub struct PetGraph<'g, N, E> {
+    graph: &'g ::petgraph::Graph<N, E>,
+    nodes: Vec<NodeIndex>,
+    labels: Labels<'g, N>,
+    properties: Properties<'g, N>,
+    rel_type: RelType<'g, E>,
+    relationship_properties: Properties<'g, E>,
+    cache: WeightCache,
+}
+
+impl<'g, N: NodeWeight, E: EdgeWeight> PetGraph<'g, N, E> {
+    /// Creates an adapter that reads labels, types and properties from the
+    /// `NodeWeight` and `EdgeWeight` implementations of the weights.
+    pub fn new(graph: &'g ::petgraph::Graph<N, E>) -> Self {
+        Self::with_extractors(graph)
+            .labels(N::labels)
+            .properties(N::properties)
+            .rel_type(E::rel_type)
+            .relationship_properties(E::properties)
+    }
+}
+
+impl<'g, N, E> PetGraph<'g, N, E> {
+    /// Creates an adapter for arbitrary weights.
+    ///
+    /// Nodes have no labels or properties and relationships have no type or
+    /// properties until the corresponding closures are set.
+    pub fn with_extractors(graph: &'g ::petgraph::Graph<N, E>) -> Self {
+        Self {
+            graph,
+            nodes: graph.node_indices().collect(),
+            labels: Box::new(|_| Vec::new()),
+            properties: Box::new(|_| Vec::new()),
+            rel_type: Box::new(|_| String::new()),
+            relationship_properties: Box::new(|_| Vec::new()),
+            cache: WeightCache::new(graph.node_count(), graph.edge_count()),
+        }
+    }
+
+    pub fn labels<L>(mut self, labels: impl Fn(&N) -> L + 'g) -> Self
+    where
+        L: IntoIterator,
+        L::Item: Into<String>,
+    {
+        self.labels = Box::new(move |node| labels(node).into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn properties<P, K, V>(mut self, properties: impl Fn(&N) -> P + 'g) -> Self
+    where
+        P: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<Value>,
+    {
+        self.properties = Box::new(move |node| into_properties(properties(node)));
+        self
+    }
+
+    pub fn rel_type<T>(mut self, rel_type: impl Fn(&E) -> T + 'g) -> Self
+    where
+        T: Into<String>,
+    {
+        self.rel_type = Box::new(move |edge| rel_type(edge).into());
+        self
+    }
+
+    pub fn relationship_properties<P, K, V>(mut self, properties: impl Fn(&E) -> P + 'g) -> Self
+    where
+        P: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<Value>,
+    {
+        self.relationship_properties = Box::new(move |edge| into_properties(properties(edge)));
+        self
+    }
+
+    fn node(&self, node_id: NodeIndex) -> &NodeData {
+        let weight = &self.graph[node_id];
+        self.cache.node_with(node_id.index(), || NodeData {
+            labels: (self.labels)(weight),
+            properties: (self.properties)(weight),
+        })
+    }
+
+    fn relationships(
+        &self,
+        node_id: NodeIndex,
+        direction: Direction,
+    ) -> RelationshipIterator<'_, &NodeIndex, &str, &str, &Value> {
+        Box::new(
+            self.graph
+                .edges_directed(node_id, direction)
+                .map(move |edge| {
+                    let other = match direction {
+                        Direction::Outgoing => edge.target(),
+                        Direction::Incoming => edge.source(),
+                    };
+                    let data = self.cache.edge_with(edge.id().index(), || EdgeData {
+                        rel_type: (self.rel_type)(edge.weight()),
+                        properties: (self.relationship_properties)(edge.weight()),
+                    });
+                    (
+                        (&self.nodes[other.index()], data.rel_type.as_str()),
+                        properties(&data.properties),
+                    )
+                }),
+        )
+    }
+}
+
+impl<'g, N, E> Graph for PetGraph<'g, N, E> {
+    type NodeId = NodeIndex;
+
+    type NodeLabel = str;
+
+    type RelationshipType = str;
+
+    type PropertyKey = str;
+
+    type PropertyValue = Value;
+
+    fn nodes(&self) -> NodesIterator<'_, &Self::NodeId> {
+        Box::new(self.nodes.iter())
+    }
+
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn relationship_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    fn node_labels(&self, node_id: &Self::NodeId) -> LabelIterator<'_, &Self::NodeLabel> {
+        Box::new(self.node(*node_id).labels.iter().map(String::as_str))
+    }
+
+    fn node_properties(
+        &self,
+        node_id: &Self::NodeId,
+    ) -> PropertyIterator<'_, &Self::PropertyKey, &Self::PropertyValue> {
+        properties(&self.node(*node_id).properties)
+    }
+
+    fn outgoing_relationships<'a, 'b: 'a>(
+        &'a self,
+        node_id: &'b Self::NodeId,
+    ) -> RelationshipIterator<
+        'a,
+        &'a Self::NodeId,
+        &'a Self::RelationshipType,
+        &'a Self::PropertyKey,
+        &'a Self::PropertyValue,
+    > {
+        self.relationships(*node_id, Direction::Outgoing)
+    }
+
+    fn incoming_relationships<'a, 'b: 'a>(
+        &'a self,
+        node_id: &'b Self::NodeId,
+    ) -> RelationshipIterator<
+        'a,
+        &'a Self::NodeId,
+        &'a Self::RelationshipType,
+        &'a Self::PropertyKey,
+        &'a Self::PropertyValue,
+    > {
+        self.relationships(*node_id, Direction::Incoming)
+    }
+
+    fn property_value(&self, value: &Self::PropertyValue) -> Value {
+        value.clone()
+    }
+}
+
+/// Adapts a directed `petgraph::csr::Csr` to the `Graph` trait.
+///
+/// Outgoing relationships are read from the compressed rows in O(degree).
+/// Incoming relationships are indexed once, on first access.
+pub struct CsrGraph<'g, N, E> {
+    graph: &'g Csr<N, E>,
+    nodes: Vec<u32>,
+    /// The position of the first outgoing edge of every node in the edge array.
+    offsets: Vec<usize>,
+    cache: WeightCache,
+    incoming: OnceCell<Vec<Vec<(u32, usize)>>>,
+}
+
+impl<'g, N: NodeWeight, E: EdgeWeight> CsrGraph<'g, N, E> {
+    pub fn new(graph: &'g Csr<N, E>) -> Self {
+        let nodes = (0..graph.node_count() as u32).collect::<Vec<_>>();
+        let mut offsets = Vec::with_capacity(nodes.len());
+        let edge_count = nodes.iter().fold(0, |offset, &node| {
+            offsets.push(offset);
+            offset + graph.out_degree(node)
+        });
+
+        Self {
+            graph,
+            nodes,
+            offsets,
+            cache: WeightCache::new(graph.node_count(), edge_count),
+            incoming: OnceCell::new(),
+        }
+    }
+
+    /// Returns, per node, the source and edge position of its incoming relationships.
+    fn incoming(&self) -> &[Vec<(u32, usize)>] {
+        self.incoming.get_or_init(|| {
+            let mut incoming = vec![Vec::new(); self.nodes.len()];
+            self.nodes.iter().for_each(|&source| {
+                self.graph
+                    .edges(source)
+                    .for_each(|edge| incoming[edge.target() as usize].push((source, edge.id())))
+            });
+            incoming
+        })
+    }
+
+    fn edge(&self, source: u32, position: usize) -> &EdgeData {
+        let weight = &self.graph.edges_slice(source)[position - self.offsets[source as usize]];
+        self.cache.edge(position, weight)
+    }
+}
+
+impl<'g, N: NodeWeight, E: EdgeWeight> Graph for CsrGraph<'g, N, E> {
+    type NodeId = u32;
+
+    type NodeLabel = str;
+
+    type RelationshipType = str;
+
+    type PropertyKey = str;
+
+    type PropertyValue = Value;
+
+    fn nodes(&self) -> NodesIterator<'_, &Self::NodeId> {
+        Box::new(self.nodes.iter())
+    }
+
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn relationship_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    fn node_labels(&self, node_id: &Self::NodeId) -> LabelIterator<'_, &Self::NodeLabel> {
+        let data = self.cache.node(*node_id as usize, &self.graph[*node_id]);
+        Box::new(data.labels.iter().map(String::as_str))
+    }
+
+    fn node_properties(
+        &self,
+        node_id: &Self::NodeId,
+    ) -> PropertyIterator<'_, &Self::PropertyKey, &Self::PropertyValue> {
+        let data = self.cache.node(*node_id as usize, &self.graph[*node_id]);
+        properties(&data.properties)
+    }
+
+    fn outgoing_relationships<'a, 'b: 'a>(
+        &'a self,
+        node_id: &'b Self::NodeId,
+    ) -> RelationshipIterator<
+        'a,
+        &'a Self::NodeId,
+        &'a Self::RelationshipType,
+        &'a Self::PropertyKey,
+        &'a Self::PropertyValue,
+    > {
+        let offset = self.offsets[*node_id as usize];
+        Box::new(
+            self.graph
+                .neighbors_slice(*node_id)
+                .iter()
+                .zip(self.graph.edges_slice(*node_id))
+                .enumerate()
+                .map(move |(position, (&target, weight))| {
+                    let data = self.cache.edge(offset + position, weight);
+                    (
+                        (&self.nodes[target as usize], data.rel_type.as_str()),
+                        properties(&data.properties),
+                    )
+                }),
+        )
+    }
+
+    fn incoming_relationships<'a, 'b: 'a>(
+        &'a self,
+        node_id: &'b Self::NodeId,
+    ) -> RelationshipIterator<
+        'a,
+        &'a Self::NodeId,
+        &'a Self::RelationshipType,
+        &'a Self::PropertyKey,
+        &'a Self::PropertyValue,
+    > {
+        let incoming = &self.incoming()[*node_id as usize];
+        Box::new(incoming.iter().map(move |&(source, position)| {
+            let data = self.edge(source, position);
+            (
+                (&self.nodes[source as usize], data.rel_type.as_str()),
+                properties(&data.properties),
+            )
+        }))
+    }
+
+    fn property_value(&self, value: &Self::PropertyValue) -> Value {
+        value.clone()
+    }
+}
+
+#[cfg(all(feature = "gdl", test))]
+mod tests {
+    use super::*;
+    use crate::{canonicalize, equals};
+
+    use ::gdl::Graph as GdlGraph;
+
+    struct Person {
+        name: &'static str,
+    }
+
+    impl NodeWeight for Person {
+        fn labels(&self) -> Vec<String> {
+            vec!["Person".to_string()]
+        }
+
+        fn properties(&self) -> PropertyList {
+            vec![("name".to_string(), self.name.into())]
+        }
+    }
+
+    #[derive(Clone)]
+    struct Knows(i64);
+
+    impl EdgeWeight for Knows {
+        fn rel_type(&self) -> String {
+            "KNOWS".to_string()
+        }
+
+        fn properties(&self) -> PropertyList {
+            vec![("since".to_string(), self.0.into())]
+        }
+    }
+
+    fn expected() -> GdlGraph {
+        r#"
+          (a:Person {name: "Alice"}), (b:Person {name: "Bob"}), (c:Person {name: "Eve"}),
+          (a)-[:KNOWS {since: 2020}]->(b), (b)-[:KNOWS {since: 2021}]->(a),
+          (c)-[:KNOWS {since: 2022}]->(a), (c)-[:KNOWS {since: 2023}]->(c)
+        "#
+        .parse()
+        .unwrap()
+    }
+
+    fn people() -> Vec<Person> {
+        ["Alice", "Bob", "Eve"]
+            .iter()
+            .map(|&name| Person { name })
+            .collect()
+    }
+
+    const EDGES: [(u32, u32, i64); 4] = [(0, 1, 2020), (1, 0, 2021), (2, 0, 2022), (2, 2, 2023)];
+
+    #[test]
+    fn test_petgraph() {
+        let mut graph = ::petgraph::Graph::new();
+        let nodes = people()
+            .into_iter()
+            .map(|person| graph.add_node(person))
+            .collect::<Vec<_>>();
+        EDGES.iter().for_each(|&(source, target, since)| {
+            graph.add_edge(nodes[source as usize], nodes[target as usize], Knows(since));
+        });
+
+        let graph = PetGraph::new(&graph);
+        assert_eq!(canonicalize(&graph), canonicalize(&expected()));
+    }
+
+    #[test]
+    fn test_isomorphic_petgraphs_with_extractors() {
+        let mut g1 = ::petgraph::Graph::<u32, (&str, i64)>::new();
+        let a = g1.add_node(1);
+        let b = g1.add_node(2);
+        g1.add_edge(a, b, ("KNOWS", 2020));
+        g1.add_edge(b, b, ("LIKES", 2021));
+
+        let mut g2 = ::petgraph::Graph::<u32, (&str, i64)>::new();
+        let b = g2.add_node(2);
+        let a = g2.add_node(1);
+        g2.add_edge(b, b, ("LIKES", 2021));
+        g2.add_edge(a, b, ("KNOWS", 2020));
+
+        let adapter = |graph| {
+            PetGraph::with_extractors(graph)
+                .labels(|_| ["Person"])
+                .properties(|&id| [("id", i64::from(id))])
+                .rel_type(|&(rel_type, _)| rel_type)
+                .relationship_properties(|&(_, since)| [("since", since)])
+        };
+
+        assert!(equals(&adapter(&g1), &adapter(&g2)));
+        assert_eq!(
+            canonicalize(&adapter(&g1)),
+            canonicalize(
+                &"(a:Person {id: 1}), (b:Person {id: 2}), \
+                  (a)-[:KNOWS {since: 2020}]->(b), (b)-[:LIKES {since: 2021}]->(b)"
+                    .parse::<GdlGraph>()
+                    .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_csr() {
+        let mut graph = Csr::new();
+        people().into_iter().for_each(|person| {
+            graph.add_node(person);
+        });
+        // Insert out of row-major order to exercise the edge offsets.
+        EDGES.iter().rev().for_each(|&(source, target, since)| {
+            graph.add_edge(source, target, Knows(since));
+        });
+
+        let graph = CsrGraph::new(&graph);
+        assert_eq!(canonicalize(&graph), canonicalize(&expected()));
+    }
+
+    #[test]
+    fn test_csr_unit_weights() {
+        let graph = Csr::<(), ()>::from_sorted_edges(&[(0, 1), (0, 2), (1, 2), (2, 0)]).unwrap();
+
+        let expected = "(a)-->(b), (a)-->(c), (b)-->(c), (c)-->(a)"
+            .parse::<GdlGraph>()
+            .unwrap();
+        assert!(equals(&CsrGraph::new(&graph), &expected));
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{canonical, CanonicalForm, CompareOptions, Graph};
+
+/// A graph whose canonical form has been computed once, to compare it
+/// against many other graphs without canonicalizing it again.
+///
+/// `matches` only canonicalizes the other graph, and skips even that if the
+/// number of nodes or relationships differs, where the options allow it.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::PreparedGraph;
+///
+/// let expected = "(a:A)-->(b:B)".parse::<GdlGraph>().unwrap();
+/// let expected = PreparedGraph::new(&expected);
+///
+/// let g1 = "(x:B)<--(y:A)".parse::<GdlGraph>().unwrap();
+/// let g2 = "(x:A)<--(y:B)".parse::<GdlGraph>().unwrap();
+///
+/// assert!(expected.matches(&g1));
+/// assert!(!expected.matches(&g2));
+/// ```
+#[derive(Clone, Debug)]
+pub struct PreparedGraph {
+    form: CanonicalForm,
+    node_count: usize,
+    relationship_count: usize,
+    options: CompareOptions,
+}
+
+impl PreparedGraph {
+    pub fn new<G: Graph>(graph: &G) -> Self {
+        Self::with_options(graph, CompareOptions::default())
+    }
+
+    /// Prepares a graph to be compared under the given options, which are
+    /// also applied to every graph passed to `matches`.
+    pub fn with_options<G: Graph>(graph: &G, options: CompareOptions) -> Self {
+        Self {
+            form: canonical::canonical_form(graph, &options),
+            node_count: graph.node_count(),
+            relationship_count: graph.relationship_count(),
+            options,
+        }
+    }
+
+    /// Returns true if `other` is isomorphic to the prepared graph, like
+    /// `equals_with` under the options the graph was prepared with.
+    pub fn matches(&self, other: &impl Graph) -> bool {
+        if self.options.preserves_counts()
+            && (self.node_count != other.node_count()
+                || self.relationship_count != other.relationship_count())
+        {
+            return false;
+        }
+        canonical::canonical_form(other, &self.options) == self.form
+    }
+
+    pub fn canonical_form(&self) -> &CanonicalForm {
+        &self.form
+    }
+
+    pub fn options(&self) -> &CompareOptions {
+        &self.options
+    }
+}
+
+#[cfg(all(feature = "gdl", test))]
+mod tests {
+    use super::*;
+    use crate::{canonical_form, equals_with};
+
+    use ::gdl::Graph as GdlGraph;
+
+    fn from_gdl(gdl: &str) -> GdlGraph {
+        gdl.parse::<GdlGraph>().unwrap()
+    }
+
+    #[test]
+    fn test_matches_like_equals() {
+        let expected = from_gdl("(a:A { v: 1 })-[:R]->(b:B), (b)-[:S]->(a)");
+        let prepared = PreparedGraph::new(&expected);
+
+        assert_eq!(prepared.canonical_form(), &canonical_form(&expected));
+
+        let candidates = [
+            "(x:B)-[:S]->(y:A { v: 1 }), (y)-[:R]->(x)",
+            "(x:B)-[:R]->(y:A { v: 1 }), (y)-[:S]->(x)",
+            "(x:A { v: 1 })-[:R]->(y:B)",
+            "(x:A { v: 1 })-[:R]->(y:B), (y)-[:S]->(x), (z)",
+        ];
+        candidates.iter().for_each(|candidate| {
+            let candidate = from_gdl(candidate);
+            assert_eq!(
+                prepared.matches(&candidate),
+                equals_with(&expected, &candidate, prepared.options())
+            );
+        });
+        assert!(prepared.matches(&from_gdl(candidates[0])));
+    }
+
+    #[test]
+    fn test_matches_applies_options() {
+        let expected = from_gdl("(a:A)-[:R]->(b:B), (a)-[:R]->(b)");
+        let options = CompareOptions::default().dedup_parallel_edges(true);
+        let prepared = PreparedGraph::with_options(&expected, options);
+
+        assert!(prepared.matches(&from_gdl("(x:A)-[:R]->(y:B)")));
+        assert!(!PreparedGraph::new(&expected).matches(&from_gdl("(x:A)-[:R]->(y:B)")));
+    }
+}
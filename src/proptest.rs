@@ -0,0 +1,230 @@
+use ::proptest::{
+    collection::vec,
+    option,
+    prelude::*,
+    sample::{select, subsequence},
+};
+
+use crate::{SimpleGraph, Value};
+
+const LABELS: [&str; 3] = ["A", "B", "C"];
+const REL_TYPES: [&str; 2] = ["R", "S"];
+
+#[derive(Clone, Debug)]
+struct NodeSpec {
+    labels: Vec<&'static str>,
+    properties: Vec<(&'static str, Value)>,
+}
+
+#[derive(Clone, Debug)]
+struct RelationshipSpec {
+    source: usize,
+    target: usize,
+    rel_type: &'static str,
+    properties: Vec<(&'static str, Value)>,
+}
+
+/// A graph together with a node permutation and the order in which the copy
+/// emits its relationships and properties.
+#[derive(Clone, Debug)]
+struct PairSpec {
+    nodes: Vec<NodeSpec>,
+    relationships: Vec<RelationshipSpec>,
+    permutation: Vec<usize>,
+    relationship_order: Vec<usize>,
+    reverse_properties: Vec<bool>,
+}
+
+fn node() -> impl Strategy<Value = NodeSpec> {
+    (
+        subsequence(LABELS.to_vec(), 0..=LABELS.len()),
+        option::of(0..3_i64),
+        option::of(select(vec!["x", "y"])),
+    )
+        .prop_map(|(labels, v, name)| NodeSpec {
+            labels,
+            properties: v
+                .map(|v| ("v", Value::from(v)))
+                .into_iter()
+                .chain(name.map(|name| ("name", Value::from(name))))
+                .collect(),
+        })
+}
+
+fn relationship(node_count: usize) -> impl Strategy<Value = RelationshipSpec> {
+    (
+        0..node_count,
+        0..node_count,
+        select(REL_TYPES.to_vec()),
+        option::of(0..3_i64),
+        option::of(any::<bool>()),
+    )
+        .prop_map(
+            |(source, target, rel_type, weight, flag)| RelationshipSpec {
+                source,
+                target,
+                rel_type,
+                properties: weight
+                    .map(|weight| ("weight", Value::from(weight)))
+                    .into_iter()
+                    .chain(flag.map(|flag| ("flag", Value::from(flag))))
+                    .collect(),
+            },
+        )
+}
+
+fn pair(max_nodes: usize, max_relationships: usize) -> impl Strategy<Value = PairSpec> {
+    (1..=max_nodes.max(1))
+        .prop_flat_map(move |node_count| {
+            (
+                vec(node(), node_count),
+                vec(relationship(node_count), 0..=max_relationships),
+                Just((0..node_count).collect::<Vec<_>>()).prop_shuffle(),
+            )
+        })
+        .prop_flat_map(|(nodes, relationships, permutation)| {
+            let relationship_count = relationships.len();
+            let property_count = nodes.len() + relationship_count;
+            (
+                Just(nodes),
+                Just(relationships),
+                Just(permutation),
+                Just((0..relationship_count).collect::<Vec<_>>()).prop_shuffle(),
+                vec(any::<bool>(), property_count),
+            )
+        })
+        .prop_map(
+            |(nodes, relationships, permutation, relationship_order, reverse_properties)| {
+                PairSpec {
+                    nodes,
+                    relationships,
+                    permutation,
+                    relationship_order,
+                    reverse_properties,
+                }
+            },
+        )
+}
+
+impl PairSpec {
+    fn original(&self) -> SimpleGraph {
+        let mut graph = SimpleGraph::new();
+        self.nodes.iter().enumerate().for_each(|(node, spec)| {
+            graph.add_node(
+                format!("n{}", node),
+                spec.labels.iter().copied(),
+                spec.properties.iter().cloned(),
+            );
+        });
+        self.relationships.iter().for_each(|rel| {
+            graph.add_edge(
+                &format!("n{}", rel.source),
+                &format!("n{}", rel.target),
+                rel.rel_type,
+                rel.properties.iter().cloned(),
+            );
+        });
+        graph
+    }
+
+    /// Renames node `i` to `m{permutation[i]}` and adds nodes, relationships
+    /// and properties in a different order.
+    fn copy(&self, relationships: &[usize]) -> SimpleGraph {
+        let id = |node: usize| format!("m{}", self.permutation[node]);
+        let properties = |position: usize, properties: &[(&'static str, Value)]| {
+            let mut properties = properties.to_vec();
+            if self.reverse_properties[position] {
+                properties.reverse();
+            }
+            properties
+        };
+
+        let mut order = (0..self.nodes.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&node| self.permutation[node]);
+
+        let mut graph = SimpleGraph::new();
+        order.into_iter().for_each(|node| {
+            let spec = &self.nodes[node];
+            let mut labels = spec.labels.clone();
+            labels.reverse();
+            graph.add_node(id(node), labels, properties(node, &spec.properties));
+        });
+        relationships.iter().for_each(|&position| {
+            let rel = &self.relationships[position];
+            graph.add_edge(
+                &id(rel.source),
+                &id(rel.target),
+                rel.rel_type,
+                properties(self.nodes.len() + position, &rel.properties),
+            );
+        });
+        graph
+    }
+}
+
+/// Generates pairs of graphs that are isomorphic by construction.
+///
+/// The second graph is a copy of the first with renamed node ids, nodes and
+/// relationships added in a different order and labels and properties
+/// listed in a different order. Graphs have up to `max_nodes` nodes and up
+/// to `max_relationships` relationships, including parallel relationships
+/// and self-loops.
+///
+/// ```
+/// use assert_graph_iso::{equals, proptest::isomorphic_pairs};
+/// use proptest::{prop_assert, proptest};
+///
+/// proptest!(|((left, right) in isomorphic_pairs(5, 8))| {
+///     prop_assert!(equals(&left, &right));
+/// });
+/// ```
+pub fn isomorphic_pairs(
+    max_nodes: usize,
+    max_relationships: usize,
+) -> impl Strategy<Value = (SimpleGraph, SimpleGraph)> {
+    pair(max_nodes, max_relationships)
+        .prop_map(|spec| (spec.original(), spec.copy(&spec.relationship_order)))
+}
+
+/// Generates pairs of graphs that are not isomorphic by construction.
+///
+/// Like `isomorphic_pairs`, but the copy misses one relationship, or has an
+/// additional one if the graph has no relationships, so the graphs differ in
+/// the number of relationships.
+pub fn non_isomorphic_pairs(
+    max_nodes: usize,
+    max_relationships: usize,
+) -> impl Strategy<Value = (SimpleGraph, SimpleGraph)> {
+    pair(max_nodes, max_relationships).prop_map(|spec| {
+        let original = spec.original();
+        let copy = match spec.relationship_order.split_first() {
+            Some((_, rest)) => spec.copy(rest),
+            None => {
+                let mut copy = spec.copy(&[]);
+                let node = format!("m{}", spec.permutation[0]);
+                copy.add_edge(&node, &node, "R", None::<(&str, Value)>);
+                copy
+            }
+        };
+        (original, copy)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{canonicalize, equals};
+
+    proptest! {
+        #[test]
+        fn test_isomorphic_pairs((left, right) in isomorphic_pairs(6, 10)) {
+            prop_assert_eq!(canonicalize(&left), canonicalize(&right));
+        }
+
+        #[test]
+        fn test_non_isomorphic_pairs((left, right) in non_isomorphic_pairs(6, 10)) {
+            prop_assert!(!equals(&left, &right));
+            prop_assert_ne!(canonicalize(&left), canonicalize(&right));
+        }
+    }
+}
@@ -0,0 +1,331 @@
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{borrow::Borrow, fmt::Display};
+
+use crate::{
+    canonical::Collected,
+    graph::{Graph, LabelIterator, NodesIterator, PropertyIterator, RelationshipIterator},
+    Value,
+};
+
+/// A property graph backed by plain vectors and maps.
+///
+/// Relationships are indexed by source and target node, so iterating the
+/// relationships of a node is proportional to its degree.
+#[derive(Clone, Debug, Default)]
+pub struct SimpleGraph {
+    nodes: Vec<Node>,
+    index: BTreeMap<String, usize>,
+    relationships: Vec<Relationship>,
+    undirected: bool,
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    id: String,
+    labels: Vec<String>,
+    properties: Vec<(String, Value)>,
+    outgoing: Vec<usize>,
+    incoming: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+struct Relationship {
+    source: usize,
+    target: usize,
+    rel_type: String,
+    properties: Vec<(String, Value)>,
+    id: Option<String>,
+}
+
+impl SimpleGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a graph whose relationships are undirected, see
+    /// `Graph::is_directed`. Relationships are still added from source to
+    /// target, but their orientation is not compared.
+    pub fn new_undirected() -> Self {
+        Self {
+            undirected: true,
+            ..Self::default()
+        }
+    }
+
+    /// Adds a node with the given labels and properties.
+    ///
+    /// Panics if a node with the same id already exists.
+    pub fn add_node<L, P, K, V>(
+        &mut self,
+        id: impl Into<String>,
+        labels: L,
+        properties: P,
+    ) -> &mut Self
+    where
+        L: IntoIterator,
+        L::Item: Into<String>,
+        P: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<Value>,
+    {
+        let id = id.into();
+        if self.index.contains_key(&id) {
+            panic!("Node id {} already exists", id);
+        }
+
+        self.index.insert(id.clone(), self.nodes.len());
+        self.nodes.push(Node {
+            id,
+            labels: labels.into_iter().map(Into::into).collect(),
+            properties: properties
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+            outgoing: Vec::new(),
+            incoming: Vec::new(),
+        });
+        self
+    }
+
+    /// Adds a relationship between two existing nodes.
+    ///
+    /// An empty relationship type denotes an untyped relationship. Like
+    /// `BTreeMap::get`, nodes may be given by any borrowed form of their id.
+    /// Panics if either node does not exist.
+    pub fn add_edge<Q, P, K, V>(
+        &mut self,
+        source: &Q,
+        target: &Q,
+        rel_type: impl Into<String>,
+        properties: P,
+    ) -> &mut Self
+    where
+        String: Borrow<Q>,
+        Q: Ord + Display + ?Sized,
+        P: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<Value>,
+    {
+        let source = self.node_index(source);
+        let target = self.node_index(target);
+        let relationship = self.relationships.len();
+
+        self.relationships.push(Relationship {
+            source,
+            target,
+            rel_type: rel_type.into(),
+            properties: properties
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+            id: None,
+        });
+        self.nodes[source].outgoing.push(relationship);
+        self.nodes[target].incoming.push(relationship);
+        self
+    }
+
+    /// Like `add_edge`, but also sets the id returned by
+    /// `Graph::relationship_id`.
+    pub fn add_edge_with_id<Q, P, K, V>(
+        &mut self,
+        source: &Q,
+        target: &Q,
+        id: impl Into<String>,
+        rel_type: impl Into<String>,
+        properties: P,
+    ) -> &mut Self
+    where
+        String: Borrow<Q>,
+        Q: Ord + Display + ?Sized,
+        P: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<Value>,
+    {
+        self.add_edge(source, target, rel_type, properties);
+        if let Some(relationship) = self.relationships.last_mut() {
+            relationship.id = Some(id.into());
+        }
+        self
+    }
+
+    /// Creates an empty graph that is directed exactly if `graph` is.
+    pub(crate) fn with_direction_of<G: Graph>(graph: &G) -> Self {
+        Self {
+            undirected: !graph.is_directed(),
+            ..Self::default()
+        }
+    }
+
+    /// Copies any graph into a `SimpleGraph`, including its relationship ids.
+    ///
+    /// Nodes are identified by their position in `graph.nodes()`.
+    pub fn from_graph<G: Graph>(graph: &G) -> Self {
+        let mut collected = Collected::new(graph);
+        collected.read_relationship_ids(graph);
+        let mut copy = SimpleGraph::with_direction_of(graph);
+
+        collected
+            .nodes
+            .into_iter()
+            .enumerate()
+            .for_each(|(node, data)| {
+                copy.add_node(node.to_string(), data.labels, data.properties);
+            });
+
+        collected.relationships.into_iter().for_each(|rel| {
+            let (source, target) = (rel.source.to_string(), rel.target.to_string());
+            match rel.id {
+                Some(id) => {
+                    copy.add_edge_with_id(&source, &target, id, rel.rel_type, rel.properties)
+                }
+                None => copy.add_edge(&source, &target, rel.rel_type, rel.properties),
+            };
+        });
+
+        copy
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn relationship_count(&self) -> usize {
+        self.relationships.len()
+    }
+
+    /// Removes all node labels for which `f` returns `false`.
+    pub fn retain_labels(&mut self, mut f: impl FnMut(&str) -> bool) -> &mut Self {
+        self.nodes
+            .iter_mut()
+            .for_each(|node| node.labels.retain(|label| f(label)));
+        self
+    }
+
+    /// Returns true if the graph has a node with the given id, which may be
+    /// any borrowed form of it, like for `BTreeMap::contains_key`.
+    pub fn contains_node<Q>(&self, node_id: &Q) -> bool
+    where
+        String: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.index.contains_key(node_id)
+    }
+
+    fn node_index<Q>(&self, node_id: &Q) -> usize
+    where
+        String: Borrow<Q>,
+        Q: Ord + Display + ?Sized,
+    {
+        *self
+            .index
+            .get(node_id)
+            .unwrap_or_else(|| panic!("Node id {} not found", node_id))
+    }
+
+    fn node(&self, node_id: &str) -> &Node {
+        &self.nodes[self.node_index(node_id)]
+    }
+}
+
+impl Graph for SimpleGraph {
+    type NodeId = String;
+
+    type NodeLabel = str;
+
+    type RelationshipType = str;
+
+    type PropertyKey = str;
+
+    type PropertyValue = Value;
+
+    fn nodes(&self) -> NodesIterator<'_, &Self::NodeId> {
+        Box::new(self.nodes.iter().map(|node| &node.id))
+    }
+
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn relationship_count(&self) -> usize {
+        self.relationships.len()
+    }
+
+    fn node_labels(&self, node_id: &Self::NodeId) -> LabelIterator<'_, &Self::NodeLabel> {
+        Box::new(self.node(node_id).labels.iter().map(String::as_str))
+    }
+
+    fn node_properties(
+        &self,
+        node_id: &Self::NodeId,
+    ) -> PropertyIterator<'_, &Self::PropertyKey, &Self::PropertyValue> {
+        Box::new(
+            self.node(node_id)
+                .properties
+                .iter()
+                .map(|(key, value)| (key.as_str(), value)),
+        )
+    }
+
+    fn outgoing_relationships<'a, 'b: 'a>(
+        &'a self,
+        node_id: &'b Self::NodeId,
+    ) -> RelationshipIterator<
+        'a,
+        &'a Self::NodeId,
+        &'a Self::RelationshipType,
+        &'a Self::PropertyKey,
+        &'a Self::PropertyValue,
+    > {
+        Box::new(self.node(node_id).outgoing.iter().map(move |&rel| {
+            let rel = &self.relationships[rel];
+            let key = (&self.nodes[rel.target].id, rel.rel_type.as_str());
+            let value: PropertyIterator<&str, &Value> = Box::new(
+                rel.properties
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), value)),
+            );
+            (key, value)
+        }))
+    }
+
+    fn incoming_relationships<'a, 'b: 'a>(
+        &'a self,
+        node_id: &'b Self::NodeId,
+    ) -> RelationshipIterator<
+        'a,
+        &'a Self::NodeId,
+        &'a Self::RelationshipType,
+        &'a Self::PropertyKey,
+        &'a Self::PropertyValue,
+    > {
+        Box::new(self.node(node_id).incoming.iter().map(move |&rel| {
+            let rel = &self.relationships[rel];
+            let key = (&self.nodes[rel.source].id, rel.rel_type.as_str());
+            let value: PropertyIterator<&str, &Value> = Box::new(
+                rel.properties
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), value)),
+            );
+            (key, value)
+        }))
+    }
+
+    fn relationship_id(&self, source: &Self::NodeId, position: usize) -> Option<String> {
+        let relationship = *self.node(source).outgoing.get(position)?;
+        self.relationships[relationship].id.clone()
+    }
+
+    fn is_directed(&self) -> bool {
+        !self.undirected
+    }
+
+    fn property_value(&self, value: &Self::PropertyValue) -> Value {
+        value.clone()
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::{canonical::Collected, Graph};
+
+/// A summary of the size and shape of a graph, see `graph_stats`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GraphStats {
+    pub node_count: usize,
+    pub relationship_count: usize,
+    /// The number of distinct node labels.
+    pub label_count: usize,
+    /// The number of distinct relationship types.
+    pub relationship_type_count: usize,
+    /// Maps every out-degree to the number of nodes with that out-degree.
+    pub out_degrees: BTreeMap<usize, usize>,
+    /// Maps every in-degree to the number of nodes with that in-degree.
+    pub in_degrees: BTreeMap<usize, usize>,
+}
+
+/// Summarizes a graph, e.g., to sanity check it before comparing it.
+///
+/// The graph is read once, like for canonicalization. Relationships of
+/// undirected graphs count as outgoing and incoming for both endpoints.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::graph_stats;
+///
+/// let graph = "(a:A)-[:R]->(b:B), (a)-[:S]->(c:B)".parse::<GdlGraph>().unwrap();
+/// let stats = graph_stats(&graph);
+///
+/// assert_eq!(stats.node_count, 3);
+/// assert_eq!(stats.label_count, 2);
+/// assert_eq!(stats.out_degrees.get(&2), Some(&1));
+/// ```
+pub fn graph_stats<G: Graph>(graph: &G) -> GraphStats {
+    let graph = Collected::new(graph);
+    let degrees = degrees(&graph);
+
+    let distribution = |degree: fn(&(usize, usize)) -> usize| {
+        degrees
+            .iter()
+            .map(degree)
+            .fold(BTreeMap::new(), |mut distribution, degree| {
+                *distribution.entry(degree).or_insert(0) += 1;
+                distribution
+            })
+    };
+
+    GraphStats {
+        node_count: graph.nodes.len(),
+        relationship_count: graph.relationships.len(),
+        label_count: graph
+            .nodes
+            .iter()
+            .flat_map(|node| node.labels.iter())
+            .collect::<HashSet<_>>()
+            .len(),
+        relationship_type_count: graph
+            .relationships
+            .iter()
+            .map(|rel| &rel.rel_type)
+            .collect::<HashSet<_>>()
+            .len(),
+        out_degrees: distribution(|(_, out_degree)| *out_degree),
+        in_degrees: distribution(|(in_degree, _)| *in_degree),
+    }
+}
+
+/// Returns the `(in-degree, out-degree)` pairs of all nodes in ascending
+/// order, counted like in `graph_stats`.
+///
+/// Isomorphic graphs have the same degree sequence, so it is a cheap way to
+/// tell graphs apart without canonicalizing them, or to see how they differ.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::degree_sequence;
+///
+/// let graph = "(a)-->(b), (a)-->(c), (c)-->(a)".parse::<GdlGraph>().unwrap();
+///
+/// assert_eq!(degree_sequence(&graph), vec![(1, 0), (1, 1), (1, 2)]);
+/// ```
+pub fn degree_sequence<G: Graph>(graph: &G) -> Vec<(usize, usize)> {
+    sorted_degrees(&Collected::new(graph))
+}
+
+/// The `(in-degree, out-degree)` pairs of all nodes in ascending order, see
+/// `degree_sequence`.
+pub(crate) fn sorted_degrees<Id: ?Sized>(graph: &Collected<'_, Id>) -> Vec<(usize, usize)> {
+    let mut degrees = degrees(graph);
+    degrees.sort_unstable();
+    degrees
+}
+
+/// The `(in-degree, out-degree)` pair of every node. Undirected
+/// relationships count as outgoing and incoming for both endpoints.
+pub(crate) fn degrees<Id: ?Sized>(graph: &Collected<'_, Id>) -> Vec<(usize, usize)> {
+    let mut degrees = vec![(0, 0); graph.nodes.len()];
+    graph.relationships.iter().for_each(|rel| {
+        degrees[rel.source].1 += 1;
+        degrees[rel.target].0 += 1;
+        if rel.undirected {
+            degrees[rel.target].1 += 1;
+            degrees[rel.source].0 += 1;
+        }
+    });
+    degrees
+}
+
+#[cfg(all(feature = "gdl", test))]
+mod tests {
+    use super::*;
+    use crate::SimpleGraph;
+
+    use ::gdl::Graph as GdlGraph;
+
+    #[test]
+    fn test_graph_stats() {
+        let graph = r#"
+              (a:A { c: 42, b: 37, a: 13 })
+            , (b:B { bar: 84 })
+            , (c:C { baz: 19, boz: 84 })
+            , (a)-[:REL { c: 42, b: 37, a: 13 }]->(b)
+            , (b)-[:REL { c: 12 }]->(a)
+            , (b)-[:REL { a: 23 }]->(c)
+            "#
+        .parse::<GdlGraph>()
+        .unwrap();
+
+        assert_eq!(
+            graph_stats(&graph),
+            GraphStats {
+                node_count: 3,
+                relationship_count: 3,
+                label_count: 3,
+                relationship_type_count: 1,
+                out_degrees: vec![(0, 1), (1, 1), (2, 1)].into_iter().collect(),
+                in_degrees: vec![(1, 3)].into_iter().collect(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_graph_stats_of_undirected_graph() {
+        let mut graph = SimpleGraph::new_undirected();
+        ["a", "b", "c"].iter().for_each(|node| {
+            graph.add_node(*node, vec!["A", "B"], None::<(&str, i64)>);
+        });
+        graph.add_edge("a", "b", "R", None::<(&str, i64)>);
+        graph.add_edge("c", "b", "S", None::<(&str, i64)>);
+
+        let stats = graph_stats(&graph);
+        assert_eq!(stats.label_count, 2);
+        assert_eq!(stats.relationship_type_count, 2);
+        assert_eq!(stats.out_degrees, stats.in_degrees);
+        assert_eq!(
+            stats.out_degrees,
+            vec![(1, 2), (2, 1)].into_iter().collect()
+        );
+        assert_eq!(graph_stats(&SimpleGraph::new()), GraphStats::default());
+    }
+
+    #[test]
+    fn test_degree_sequence() {
+        let gdl = |gdl: &str| gdl.parse::<GdlGraph>().unwrap();
+        let g1 = gdl("(a:A)-[:R]->(b:B)-[:R]->(c:C), (a)-[:S]->(c)");
+        let g2 = gdl("(x:C)<-[:R]-(y:B)<-[:R]-(z:A), (z)-[:S]->(x)");
+        let g3 = gdl("(a:A)-[:R]->(b:B)-[:R]->(c:C), (c)-[:S]->(a)");
+
+        assert!(crate::equals(&g1, &g2));
+        assert_eq!(degree_sequence(&g1), vec![(0, 2), (1, 1), (2, 0)]);
+        assert_eq!(degree_sequence(&g1), degree_sequence(&g2));
+        assert_ne!(degree_sequence(&g1), degree_sequence(&g3));
+        assert!(degree_sequence(&SimpleGraph::new()).is_empty());
+    }
+}
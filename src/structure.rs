@@ -0,0 +1,392 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::{self, Display},
+};
+
+use crate::{
+    canonical::Collected, canonicalize, diff::TokenCounts, structural_delta, transform::transpose,
+    Graph,
+};
+
+/// Describes why a graph is not a tree or forest.
+///
+/// Relationship directions are ignored, i.e., the graph is checked as if
+/// every relationship was undirected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TreeViolation {
+    /// The graph contains a cycle, self-loops and parallel relationships included.
+    Cycle,
+    /// The graph consists of more or fewer than one weakly connected component.
+    Disconnected { components: usize },
+    /// The graph has more relationships than a tree on its nodes can have.
+    ExtraEdges { expected: usize, actual: usize },
+}
+
+impl Display for TreeViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreeViolation::Cycle => write!(f, "graph has a cycle"),
+            TreeViolation::Disconnected { components } => {
+                write!(f, "graph is disconnected into {} components", components)
+            }
+            TreeViolation::ExtraEdges { expected, actual } => write!(
+                f,
+                "graph has {} relationships but a tree on its nodes has {}",
+                actual, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TreeViolation {}
+
+/// Checks that the graph is a single tree: connected, acyclic and
+/// with exactly one relationship less than it has nodes.
+pub fn assert_is_tree<G: Graph>(graph: &G) -> Result<(), TreeViolation> {
+    let graph = Collected::new(graph);
+    let node_count = graph.nodes.len();
+    let relationship_count = graph.relationships.len();
+
+    if relationship_count + 1 > node_count.max(1) {
+        return Err(TreeViolation::ExtraEdges {
+            expected: node_count.saturating_sub(1),
+            actual: relationship_count,
+        });
+    }
+
+    let components = forest_components(&graph)?;
+    if components != 1 {
+        return Err(TreeViolation::Disconnected { components });
+    }
+
+    Ok(())
+}
+
+/// Checks that the graph is a forest, i.e., every weakly connected component is a tree.
+pub fn assert_is_forest<G: Graph>(graph: &G) -> Result<(), TreeViolation> {
+    forest_components(&Collected::new(graph)).map(|_| ())
+}
+
+/// Returns the number of components if the graph has no undirected cycle.
+fn forest_components<Id: ?Sized>(graph: &Collected<'_, Id>) -> Result<usize, TreeViolation> {
+    let mut components = UnionFind::new(graph.nodes.len());
+
+    for rel in &graph.relationships {
+        if !components.union(rel.source, rel.target) {
+            return Err(TreeViolation::Cycle);
+        }
+    }
+
+    Ok(components.count())
+}
+
+/// Returns the two parts of the graph if it is bipartite, i.e., if its nodes
+/// can be split such that every relationship connects both parts.
+///
+/// Relationship directions are ignored. Graphs with a self-loop are not
+/// bipartite. Components are colored independently, starting with the first
+/// node of every component, in the order of `Graph::nodes`, in the first part.
+///
+/// ```
+/// use ::gdl::Graph as GdlGraph;
+/// use assert_graph_iso::is_bipartite;
+///
+/// let g = "(a)-->(b)<--(c)".parse::<GdlGraph>().unwrap();
+/// let (left, right) = is_bipartite(&g).unwrap();
+/// assert_eq!(left.len() + right.len(), 3);
+///
+/// let triangle = "(a)-->(b)-->(c)-->(a)".parse::<GdlGraph>().unwrap();
+/// assert!(is_bipartite(&triangle).is_none());
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn is_bipartite<G: Graph>(graph: &G) -> Option<(HashSet<&G::NodeId>, HashSet<&G::NodeId>)> {
+    let graph = Collected::new(graph);
+
+    let mut neighbors = vec![Vec::new(); graph.nodes.len()];
+    for rel in &graph.relationships {
+        neighbors[rel.source].push(rel.target);
+        neighbors[rel.target].push(rel.source);
+    }
+
+    let mut colors = vec![None; graph.nodes.len()];
+    let mut queue = VecDeque::new();
+    for start in 0..graph.nodes.len() {
+        if colors[start].is_some() {
+            continue;
+        }
+        colors[start] = Some(false);
+        queue.push_back(start);
+
+        while let Some(node) = queue.pop_front() {
+            let color = colors[node];
+            for &neighbor in &neighbors[node] {
+                if colors[neighbor].is_none() {
+                    colors[neighbor] = color.map(|color| !color);
+                    queue.push_back(neighbor);
+                } else if colors[neighbor] == color {
+                    return None;
+                }
+            }
+        }
+    }
+
+    let mut parts = (HashSet::new(), HashSet::new());
+    graph.nodes.iter().zip(colors).for_each(|(node, color)| {
+        if color == Some(false) {
+            parts.0.insert(node.id);
+        } else {
+            parts.1.insert(node.id);
+        }
+    });
+
+    Some(parts)
+}
+
+/// Describes why a graph is not the transpose of another graph.
+///
+/// Nodes and relationships are rendered as in `StructuralDelta`,
+/// relationships in the orientation of the first graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransposeViolation {
+    /// A node of the first graph has no counterpart in the second graph.
+    MissingNode { node: String },
+    /// A node of the second graph has no counterpart in the first graph.
+    UnexpectedNode { node: String },
+    /// A relationship of the first graph is not reversed in the second graph.
+    MissingEdge { edge: String },
+    /// A reversed relationship of the second graph is not in the first graph.
+    UnexpectedEdge { edge: String },
+    /// Both graphs have the same nodes and relationships, but these are
+    /// connected differently.
+    Wiring,
+}
+
+impl Display for TransposeViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransposeViolation::MissingNode { node } => {
+                write!(f, "node {} is missing in the transpose", node)
+            }
+            TransposeViolation::UnexpectedNode { node } => {
+                write!(f, "node {} is not in the original graph", node)
+            }
+            TransposeViolation::MissingEdge { edge } => {
+                write!(f, "relationship {} is not reversed in the transpose", edge)
+            }
+            TransposeViolation::UnexpectedEdge { edge } => {
+                write!(
+                    f,
+                    "reversed relationship {} is not in the original graph",
+                    edge
+                )
+            }
+            TransposeViolation::Wiring => write!(
+                f,
+                "graphs have the same nodes and relationships but are wired differently"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TransposeViolation {}
+
+/// Checks that `b` is the transpose of `a`, i.e., that every relationship
+/// `(u)-[:T]->(v)` of `a` appears as `(v)-[:T]->(u)` in `b` and vice versa.
+///
+/// On failure, the first mismatching node or relationship is reported.
+pub fn assert_is_transpose<G: Graph, H: Graph>(a: &G, b: &H) -> Result<(), TransposeViolation> {
+    let transposed = transpose(b);
+    if canonicalize(a) == canonicalize(&transposed) {
+        return Ok(());
+    }
+
+    let delta = structural_delta(a, &transposed);
+    let first = |tokens: TokenCounts| tokens.into_iter().next().map(|(token, _)| token);
+
+    if let Some(node) = first(delta.removed_nodes) {
+        Err(TransposeViolation::MissingNode { node })
+    } else if let Some(node) = first(delta.added_nodes) {
+        Err(TransposeViolation::UnexpectedNode { node })
+    } else if let Some(edge) = first(delta.removed_edges) {
+        Err(TransposeViolation::MissingEdge { edge })
+    } else if let Some(edge) = first(delta.added_edges) {
+        Err(TransposeViolation::UnexpectedEdge { edge })
+    } else {
+        Err(TransposeViolation::Wiring)
+    }
+}
+
+/// Disjoint sets over `0..n` with path halving and union by size.
+pub(crate) struct UnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    count: usize,
+}
+
+impl UnionFind {
+    pub(crate) fn new(n: usize) -> Self {
+        Self {
+            parents: (0..n).collect(),
+            sizes: vec![1; n],
+            count: n,
+        }
+    }
+
+    pub(crate) fn find(&mut self, mut node: usize) -> usize {
+        while self.parents[node] != node {
+            self.parents[node] = self.parents[self.parents[node]];
+            node = self.parents[node];
+        }
+        node
+    }
+
+    /// Merges the sets of both nodes, returns `false` if they were already merged.
+    pub(crate) fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.sizes[a] < self.sizes[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+        self.count -= 1;
+        true
+    }
+
+    /// Returns the number of disjoint sets.
+    pub(crate) fn count(&self) -> usize {
+        self.count
+    }
+}
+
+#[cfg(all(feature = "gdl", test))]
+mod tests {
+    use super::*;
+
+    use ::gdl::Graph as GdlGraph;
+
+    fn from_gdl(gdl: &str) -> GdlGraph {
+        gdl.parse::<GdlGraph>().unwrap()
+    }
+
+    fn sorted(part: HashSet<&str>) -> Vec<&str> {
+        let mut part = part.into_iter().collect::<Vec<_>>();
+        part.sort_unstable();
+        part
+    }
+
+    #[test]
+    fn test_bipartite() {
+        let g = from_gdl("(a)-->(x), (a)-->(y), (b)-->(y), (z)-->(b), (z)-->(c)");
+        let (left, right) = is_bipartite(&g).unwrap();
+        let mut parts = [sorted(left), sorted(right)];
+        parts.sort();
+
+        assert_eq!(parts, [vec!["a", "b", "c"], vec!["x", "y", "z"]]);
+    }
+
+    #[test]
+    fn test_odd_cycle_is_not_bipartite() {
+        let g = from_gdl("(a)-->(b)-->(c)-->(d)-->(e)-->(a)");
+        assert_eq!(is_bipartite(&g), None);
+
+        let g = from_gdl("(a)-->(b)-->(c)-->(d)-->(a)");
+        assert!(is_bipartite(&g).is_some());
+
+        let g = from_gdl("(a)-->(a)");
+        assert_eq!(is_bipartite(&g), None);
+    }
+
+    #[test]
+    fn test_disconnected_bipartite() {
+        let g = from_gdl("(a)-->(b), (c)<--(d), (d)-->(e), (f)");
+        let (left, right) = is_bipartite(&g).unwrap();
+
+        assert_eq!(left.len() + right.len(), 6);
+        [("a", "b"), ("d", "c"), ("d", "e")]
+            .iter()
+            .for_each(|(source, target)| {
+                assert_ne!(left.contains(source), left.contains(target));
+                assert_ne!(right.contains(source), right.contains(target));
+            });
+
+        let g = from_gdl("(a)-->(b), (c)-->(d)-->(e)-->(c)");
+        assert_eq!(is_bipartite(&g), None);
+    }
+
+    #[test]
+    fn test_tree() {
+        let g = from_gdl("(a)-->(b), (a)-->(c), (d)-->(c), (c)-->(e)");
+        assert_eq!(assert_is_tree(&g), Ok(()));
+        assert_eq!(assert_is_forest(&g), Ok(()));
+    }
+
+    #[test]
+    fn test_single_node_tree() {
+        let g = from_gdl("(a)");
+        assert_eq!(assert_is_tree(&g), Ok(()));
+    }
+
+    #[test]
+    fn test_cycle() {
+        let g = from_gdl("(a)-->(b)-->(c)-->(a), (d)");
+        assert_eq!(assert_is_tree(&g), Err(TreeViolation::Cycle));
+        assert_eq!(assert_is_forest(&g), Err(TreeViolation::Cycle));
+
+        let g = from_gdl("(a)-->(b)-->(c)-->(a)");
+        assert_eq!(
+            assert_is_tree(&g),
+            Err(TreeViolation::ExtraEdges {
+                expected: 2,
+                actual: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_self_loop_is_cycle() {
+        let g = from_gdl("(a)-->(a)");
+        assert!(assert_is_forest(&g).is_err());
+        assert!(assert_is_tree(&g).is_err());
+    }
+
+    #[test]
+    fn test_forest() {
+        let g = from_gdl("(a)-->(b), (a)-->(c), (d)-->(e), (f)");
+        assert_eq!(assert_is_forest(&g), Ok(()));
+        assert_eq!(
+            assert_is_tree(&g),
+            Err(TreeViolation::Disconnected { components: 3 })
+        );
+    }
+
+    #[test]
+    fn test_is_transpose() {
+        let a = from_gdl("(a:A), (b:B), (c:C), (a)-[:REL]->(b), (b)-[:REL {w: 1}]->(c)");
+        let b = from_gdl("(a:A), (b:B), (c:C), (b)-[:REL]->(a), (c)-[:REL {w: 1}]->(b)");
+
+        assert_eq!(assert_is_transpose(&a, &b), Ok(()));
+        assert_eq!(assert_is_transpose(&b, &a), Ok(()));
+    }
+
+    #[test]
+    fn test_is_not_transpose() {
+        let a = from_gdl("(a:A), (b:B), (c:C), (a)-[:REL]->(b), (b)-[:REL {w: 1}]->(c)");
+        let b = from_gdl("(a:A), (b:B), (c:C), (b)-[:REL]->(a), (b)-[:REL {w: 1}]->(c)");
+
+        assert_eq!(
+            assert_is_transpose(&a, &b),
+            Err(TransposeViolation::MissingEdge {
+                edge: "(:B )-[:REL { w: 1 }]->(:C )".to_string()
+            })
+        );
+        assert_eq!(
+            assert_is_transpose(&a, &a),
+            Err(TransposeViolation::MissingEdge {
+                edge: "(:A )-[:REL ]->(:B )".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use core::{
+    fmt::{self, Display},
+    time::Duration,
+};
+#[cfg(feature = "std")]
+use std::{cell::Cell, time::Instant};
+
+/// Canonicalization did not finish within its time budget.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Timeout {
+    /// The budget that was exceeded.
+    pub budget: Duration,
+    /// The time spent until canonicalization was aborted.
+    pub elapsed: Duration,
+    /// The number of nodes read before canonicalization was aborted.
+    pub nodes_read: usize,
+    /// The number of nodes whose relationships were read.
+    pub relationships_read_for: usize,
+}
+
+impl Display for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "canonicalization exceeded the time budget of {:?} after {:?}, \
+             having read {} nodes and the relationships of {} nodes",
+            self.budget, self.elapsed, self.nodes_read, self.relationships_read_for
+        )
+    }
+}
+
+impl core::error::Error for Timeout {}
+
+/// `Deadline::check` only reads the clock every `CHECK_INTERVAL` calls to
+/// keep the overhead low.
+#[cfg(feature = "std")]
+const CHECK_INTERVAL: usize = 64;
+
+/// A point in time after which canonicalization is aborted.
+#[cfg(feature = "std")]
+pub(crate) struct Deadline {
+    start: Instant,
+    budget: Duration,
+    /// The number of calls to `check` so far.
+    checks: Cell<usize>,
+}
+
+/// Without `std`, there is no clock to set a deadline with.
+#[cfg(not(feature = "std"))]
+pub(crate) enum Deadline {}
+
+#[cfg(not(feature = "std"))]
+impl Deadline {
+    pub(crate) fn check(
+        &self,
+        _nodes_read: usize,
+        _relationships_read_for: usize,
+    ) -> Result<(), Timeout> {
+        match *self {}
+    }
+
+    pub(crate) fn check_now(
+        &self,
+        _nodes_read: usize,
+        _relationships_read_for: usize,
+    ) -> Result<(), Timeout> {
+        match *self {}
+    }
+}
+
+#[cfg(feature = "std")]
+impl Deadline {
+    pub(crate) fn new(budget: Duration) -> Self {
+        Self {
+            start: Instant::now(),
+            budget,
+            checks: Cell::new(0),
+        }
+    }
+
+    /// Fails if the budget is exhausted, given the progress made so far.
+    /// Cheap enough to call for every node and relationship, since the clock
+    /// is only read every `CHECK_INTERVAL` calls.
+    pub(crate) fn check(
+        &self,
+        nodes_read: usize,
+        relationships_read_for: usize,
+    ) -> Result<(), Timeout> {
+        let checks = self.checks.get();
+        self.checks.set(checks + 1);
+        if checks % CHECK_INTERVAL != 0 {
+            return Ok(());
+        }
+        self.check_now(nodes_read, relationships_read_for)
+    }
+
+    /// Like `check`, but always reads the clock, e.g., before an expensive step.
+    pub(crate) fn check_now(
+        &self,
+        nodes_read: usize,
+        relationships_read_for: usize,
+    ) -> Result<(), Timeout> {
+        let elapsed = self.start.elapsed();
+        if elapsed > self.budget {
+            return Err(Timeout {
+                budget: self.budget,
+                elapsed,
+                nodes_read,
+                relationships_read_for,
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,456 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+};
+
+use crate::{
+    canonical::{property_tokens, Collected, Relationship},
+    Graph, SimpleGraph,
+};
+
+/// Copies `graph` into a `SimpleGraph`, renaming every node id with `f`.
+///
+/// Relationships are rewired to the renamed nodes, labels, properties and
+/// relationship ids are copied as they are. The result is isomorphic to
+/// `graph`, which makes it useful for checking that a computation does not
+/// depend on the choice of node ids.
+///
+/// Panics if `f` maps two distinct nodes to the same id.
+pub fn relabel<G: Graph>(graph: &G, f: impl Fn(&G::NodeId) -> String) -> SimpleGraph {
+    let ids = graph
+        .nodes()
+        .map(|node| (node, f(node)))
+        .collect::<HashMap<_, _>>();
+
+    let mut relabeled = SimpleGraph::with_direction_of(graph);
+
+    graph.nodes().for_each(|node| {
+        relabeled.add_node(
+            ids[node].clone(),
+            graph.node_labels(node).map(|label| label.to_string()),
+            graph
+                .node_properties(node)
+                .map(|(key, value)| (key.to_string(), graph.property_value(value))),
+        );
+    });
+
+    graph.nodes().for_each(|source| {
+        graph.outgoing_relationships(source).enumerate().for_each(
+            |(position, ((target, rel_type), properties))| {
+                let id = graph.relationship_id(source, position);
+                let (source, target) = (&ids[source], &ids[target]);
+                let rel_type = rel_type.to_string();
+                let properties =
+                    properties.map(|(key, value)| (key.to_string(), graph.property_value(value)));
+                match id {
+                    Some(id) => {
+                        relabeled.add_edge_with_id(source, target, id, rel_type, properties)
+                    }
+                    None => relabeled.add_edge(source, target, rel_type, properties),
+                };
+            },
+        )
+    });
+
+    relabeled
+}
+
+/// Copies `graph` into a `SimpleGraph` with every relationship reversed.
+///
+/// Nodes keep their labels and properties, are identified by their position
+/// in `graph.nodes()`, and relationships keep their type and properties.
+pub fn transpose<G: Graph>(graph: &G) -> SimpleGraph {
+    let collected = Collected::new(graph);
+    let mut transposed = SimpleGraph::with_direction_of(graph);
+
+    collected
+        .nodes
+        .into_iter()
+        .enumerate()
+        .for_each(|(node, data)| {
+            transposed.add_node(node.to_string(), data.labels, data.properties);
+        });
+
+    collected.relationships.into_iter().for_each(|rel| {
+        transposed.add_edge(
+            &rel.target.to_string(),
+            &rel.source.to_string(),
+            rel.rel_type,
+            rel.properties,
+        );
+    });
+
+    transposed
+}
+
+/// Contracts every pass-through node, i.e., a node without labels and
+/// properties that has exactly one incoming and one outgoing relationship.
+///
+/// The two relationships `(s)-[r1]->(x)-[r2]->(t)` are replaced by a single
+/// relationship `(s)-[r]->(t)`. Both relationships must have the same type,
+/// which `r` inherits, and `r` carries the union of their properties.
+/// Panics if the types differ or if both relationships carry the same
+/// property key with different values.
+///
+/// Chains are contracted completely. A cycle consisting only of
+/// pass-through nodes is reduced to a single node with a self-loop.
+/// Nodes of the result are identified by their position in `graph.nodes()`.
+pub fn smooth_degree_2<G: Graph>(graph: &G) -> SimpleGraph {
+    let collected = Collected::new(graph);
+    let node_count = collected.nodes.len();
+
+    let mut outgoing = vec![Vec::new(); node_count];
+    let mut incoming = vec![Vec::new(); node_count];
+    collected
+        .relationships
+        .iter()
+        .enumerate()
+        .for_each(|(position, rel)| {
+            outgoing[rel.source].push(position);
+            incoming[rel.target].push(position);
+        });
+
+    let mut relationships = collected
+        .relationships
+        .into_iter()
+        .map(Some)
+        .collect::<Vec<_>>();
+    let mut contracted = vec![false; node_count];
+
+    // Contracting a node does not change the degree of any other node,
+    // so a single pass finds all pass-through nodes.
+    for (node, data) in collected.nodes.iter().enumerate() {
+        if !data.labels.is_empty() || !data.properties.is_empty() {
+            continue;
+        }
+        let (first_position, second_position) =
+            match (incoming[node].as_slice(), outgoing[node].as_slice()) {
+                (&[first], &[second]) if first != second => (first, second),
+                _ => continue,
+            };
+
+        let first = relationships[first_position].take().unwrap();
+        let second = relationships[second_position].take().unwrap();
+        let merged = contract(first, second, data.id);
+
+        let position = relationships.len();
+        replace(&mut outgoing[merged.source], first_position, position);
+        replace(&mut incoming[merged.target], second_position, position);
+        relationships.push(Some(merged));
+        contracted[node] = true;
+    }
+
+    let mut smoothed = SimpleGraph::with_direction_of(graph);
+
+    collected
+        .nodes
+        .into_iter()
+        .enumerate()
+        .filter(|(node, _)| !contracted[*node])
+        .for_each(|(node, data)| {
+            smoothed.add_node(node.to_string(), data.labels, data.properties);
+        });
+
+    relationships.into_iter().flatten().for_each(|rel| {
+        smoothed.add_edge(
+            &rel.source.to_string(),
+            &rel.target.to_string(),
+            rel.rel_type,
+            rel.properties,
+        );
+    });
+
+    smoothed
+}
+
+/// Contracts all nodes that share the same value for the property `key`
+/// into a single node.
+///
+/// The merged node carries the union of the labels and the union of the
+/// distinct properties of its nodes. Conflicting values for the same key
+/// are all kept. Relationships are rewired to the merged nodes, and
+/// relationships that become identical, i.e., that have the same source,
+/// target, type and properties, are kept once. Relationships between merged
+/// nodes become self-loops. Nodes without `key` are not merged.
+///
+/// Values are compared by their rendering, so `42` and `42.0` are merged.
+/// Nodes of the result are identified by the position of their first
+/// node in `graph.nodes()`.
+pub fn merge_nodes<G: Graph>(graph: &G, key: &str) -> SimpleGraph {
+    let collected = Collected::new(graph);
+
+    let mut groups = HashMap::new();
+    let representatives = collected
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(node, data)| {
+            match data
+                .properties
+                .iter()
+                .find(|(candidate, _)| candidate == key)
+            {
+                Some((_, value)) => *groups.entry(value.to_string()).or_insert(node),
+                None => node,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut nodes = collected
+        .nodes
+        .iter()
+        .map(|_| (Vec::new(), Vec::new()))
+        .collect::<Vec<_>>();
+    collected
+        .nodes
+        .into_iter()
+        .zip(&representatives)
+        .for_each(|(data, &representative)| {
+            let (labels, properties) = &mut nodes[representative];
+            data.labels.into_iter().for_each(|label| {
+                if !labels.contains(&label) {
+                    labels.push(label);
+                }
+            });
+            data.properties.into_iter().for_each(|property| {
+                if !properties.contains(&property) {
+                    properties.push(property);
+                }
+            });
+        });
+
+    let mut merged = SimpleGraph::with_direction_of(graph);
+
+    nodes
+        .into_iter()
+        .enumerate()
+        .filter(|(node, _)| representatives[*node] == *node)
+        .for_each(|(node, (labels, properties))| {
+            merged.add_node(node.to_string(), labels, properties);
+        });
+
+    let mut seen = HashSet::new();
+    collected.relationships.into_iter().for_each(|rel| {
+        let source = representatives[rel.source];
+        let target = representatives[rel.target];
+        let identity = (
+            source,
+            target,
+            rel.rel_type.clone(),
+            property_tokens(&rel.properties),
+        );
+        if seen.insert(identity) {
+            merged.add_edge(
+                &source.to_string(),
+                &target.to_string(),
+                rel.rel_type,
+                rel.properties,
+            );
+        }
+    });
+
+    merged
+}
+
+/// Merges `(s)-[first]->(x)-[second]->(t)` into `(s)-[merged]->(t)`.
+fn contract<Id: Debug + ?Sized>(
+    first: Relationship,
+    second: Relationship,
+    node: &Id,
+) -> Relationship {
+    if first.rel_type != second.rel_type {
+        panic!(
+            "Conflicting relationship types {} and {} around contracted node {:?}",
+            first.rel_type, second.rel_type, node
+        );
+    }
+
+    let mut properties = first.properties;
+    second.properties.into_iter().for_each(|(key, value)| {
+        match properties.iter().find(|(existing, _)| *existing == key) {
+            Some((_, existing)) if *existing == value => {}
+            Some((_, existing)) => panic!(
+                "Conflicting values {} and {} for property {} around contracted node {:?}",
+                existing, value, key, node
+            ),
+            None => properties.push((key, value)),
+        }
+    });
+
+    Relationship {
+        source: first.source,
+        target: second.target,
+        rel_type: first.rel_type,
+        properties,
+        undirected: false,
+        id: None,
+    }
+}
+
+fn replace(adjacency: &mut [usize], old: usize, new: usize) {
+    if let Some(position) = adjacency.iter_mut().find(|position| **position == old) {
+        *position = new;
+    }
+}
+
+#[cfg(all(feature = "gdl", test))]
+mod tests {
+    use super::*;
+    use crate::{canonicalize, equals};
+
+    use ::gdl::Graph as GdlGraph;
+
+    #[test]
+    fn test_relabel_preserves_canonical_form() {
+        let g = r#"
+              (a:A { c: 42, b: 37 })
+            , (b:B { bar: 84 })
+            , (c:C)
+            , (a)-[:REL { c: 42 }]->(b)
+            , (b)-[:REL]->(a)
+            , (b)-[:REL { a: 23 }]->(c)
+            , (c)-->(c)
+            "#
+        .parse::<GdlGraph>()
+        .unwrap();
+
+        let relabeled = relabel(&g, |id| format!("node_{}", id));
+
+        assert_eq!(canonicalize(&g), canonicalize(&relabeled));
+    }
+
+    fn undirected() -> SimpleGraph {
+        let mut graph = SimpleGraph::new_undirected();
+        graph
+            .add_node("a", ["A"], [("id", 1)])
+            .add_node("b", ["B"], [("id", 2)])
+            .add_node("c", ["C"], [("id", 3)])
+            .add_edge("a", "b", "REL", [("w", 1)])
+            .add_edge("c", "b", "REL", [("w", 2)]);
+        graph
+    }
+
+    #[test]
+    fn test_relabel_keeps_undirected_graphs_undirected() {
+        let g = undirected();
+        let relabeled = relabel(&g, |id| id.to_uppercase());
+
+        assert!(!relabeled.is_directed());
+        assert!(equals(&g, &relabeled));
+    }
+
+    #[test]
+    fn test_transpose_keeps_undirected_graphs_undirected() {
+        let g = undirected();
+        let transposed = transpose(&g);
+
+        assert!(!transposed.is_directed());
+        assert!(equals(&g, &transposed));
+    }
+
+    #[test]
+    fn test_smooth_degree_2_keeps_undirected_graphs_undirected() {
+        let g = undirected();
+        let smoothed = smooth_degree_2(&g);
+
+        assert!(!smoothed.is_directed());
+        assert!(equals(&g, &smoothed));
+    }
+
+    #[test]
+    fn test_merge_nodes_keeps_undirected_graphs_undirected() {
+        let g = undirected();
+        let merged = merge_nodes(&g, "id");
+
+        assert!(!merged.is_directed());
+        assert!(equals(&g, &merged));
+    }
+
+    #[test]
+    fn test_relabel_rewires_relationships() {
+        let g = "(a), (b), (a)-[:REL]->(b)".parse::<GdlGraph>().unwrap();
+
+        let relabeled = relabel(&g, |id| id.to_uppercase());
+        let a = String::from("A");
+
+        let targets = relabeled
+            .outgoing_relationships(&a)
+            .map(|((target, rel_type), _)| (target.as_str(), rel_type))
+            .collect::<Vec<_>>();
+        assert_eq!(targets, vec![("B", "REL")]);
+        assert_eq!(relabeled.incoming_relationships(&a).count(), 0);
+    }
+
+    #[test]
+    fn test_smooth_degree_2_contracts_subdivision() {
+        let path = "(a:A), (b:B), (c:C), (a)-[:REL]->(b)-[:REL]->(c)"
+            .parse::<GdlGraph>()
+            .unwrap();
+        let subdivided = "(a:A), (b:B), (c:C), (x), (y),
+                          (a)-[:REL]->(x)-[:REL {w: 1}]->(y)-[:REL {w: 1}]->(b)-[:REL]->(c)"
+            .parse::<GdlGraph>()
+            .unwrap();
+        let expected = "(a:A), (b:B), (c:C), (a)-[:REL {w: 1}]->(b)-[:REL]->(c)"
+            .parse::<GdlGraph>()
+            .unwrap();
+
+        assert_ne!(canonicalize(&path), canonicalize(&subdivided));
+        assert_eq!(canonicalize(&smooth_degree_2(&path)), canonicalize(&path));
+        assert_eq!(
+            canonicalize(&smooth_degree_2(&subdivided)),
+            canonicalize(&expected)
+        );
+    }
+
+    #[test]
+    fn test_smooth_degree_2_keeps_cycle_node() {
+        let cycle = "(x)-->(y)-->(z)-->(x)".parse::<GdlGraph>().unwrap();
+        let smoothed = smooth_degree_2(&cycle);
+
+        assert_eq!(smoothed.node_count(), 1);
+        assert_eq!(smoothed.relationship_count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Conflicting relationship types")]
+    fn test_smooth_degree_2_conflicting_types() {
+        let g = "(a:A), (b:B), (a)-[:R1]->(x)-[:R2]->(b)"
+            .parse::<GdlGraph>()
+            .unwrap();
+        smooth_degree_2(&g);
+    }
+
+    #[test]
+    fn test_merge_nodes() {
+        let g = r#"
+              (a1:Person {id: 1, name: "Alice"}), (a2:Person:Admin {id: 1}), (b:Person {id: 2}), (c:City),
+              (a1)-[:KNOWS]->(b), (a2)-[:KNOWS]->(b), (a2)-[:LIVES_IN]->(c), (b)-[:KNOWS]->(a2)
+            "#
+        .parse::<GdlGraph>()
+        .unwrap();
+        let expected = r#"
+              (a:Person:Admin {id: 1, name: "Alice"}), (b:Person {id: 2}), (c:City),
+              (a)-[:KNOWS]->(b), (a)-[:LIVES_IN]->(c), (b)-[:KNOWS]->(a)
+            "#
+        .parse::<GdlGraph>()
+        .unwrap();
+
+        assert_eq!(
+            canonicalize(&merge_nodes(&g, "id")),
+            canonicalize(&expected)
+        );
+    }
+
+    #[test]
+    fn test_transpose() {
+        let g = "(a:A), (b:B), (a)-[:REL {w: 1}]->(b), (b)-->(b)"
+            .parse::<GdlGraph>()
+            .unwrap();
+        let expected = "(a:A), (b:B), (b)-[:REL {w: 1}]->(a), (b)-->(b)"
+            .parse::<GdlGraph>()
+            .unwrap();
+
+        assert_eq!(canonicalize(&transpose(&g)), canonicalize(&expected));
+        assert_eq!(canonicalize(&transpose(&transpose(&g))), canonicalize(&g));
+    }
+}
@@ -0,0 +1,259 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    cmp::Ordering,
+    fmt::{self, Display},
+};
+
+/// An owned property value.
+///
+/// Values are rendered the same way gdl renders a `CypherValue`,
+/// so a graph built from `Value`s canonicalizes like its gdl equivalent.
+///
+/// Floats are rendered by their `Display` implementation, which renders
+/// every NaN as `NaN`, regardless of its sign and payload. Hence, NaN
+/// properties are equal to each other, even though `NaN != NaN`, and stay
+/// NaN under all options that round or normalize numbers. Infinities are
+/// rendered as `inf` and `-inf`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    List(Vec<Value>),
+}
+
+impl Value {
+    /// Orders values first by their kind, `null` before booleans, numbers,
+    /// strings and lists, and then by value: numbers numerically, strings and
+    /// lists lexicographically. Numbers with the same value, like `0` and
+    /// `-0.0`, are ordered by their rendering.
+    ///
+    /// NaN is ordered after all other numbers and equal to every other NaN,
+    /// which keeps the order total.
+    pub(crate) fn canonical_cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::Boolean(left), Value::Boolean(right)) => left.cmp(right),
+            (Value::Integer(_) | Value::Float(_), Value::Integer(_) | Value::Float(_)) => self
+                .numeric_cmp(other)
+                .then_with(|| self.to_string().cmp(&other.to_string())),
+            (Value::String(left), Value::String(right)) => left.cmp(right),
+            (Value::List(left), Value::List(right)) => left
+                .iter()
+                .zip(right)
+                .map(|(left, right)| left.canonical_cmp(right))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or_else(|| left.len().cmp(&right.len())),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+
+    /// The position of the kind of a value in `canonical_cmp`.
+    fn rank(&self) -> u8 {
+        match self {
+            Value::Null => 0,
+            Value::Boolean(_) => 1,
+            Value::Integer(_) | Value::Float(_) => 2,
+            Value::String(_) => 3,
+            Value::List(_) => 4,
+        }
+    }
+
+    /// Compares two numbers by their exact value, with NaN after all others.
+    fn numeric_cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::Integer(left), Value::Integer(right)) => left.cmp(right),
+            (Value::Integer(integer), Value::Float(float)) => integer_float_cmp(*integer, *float),
+            (Value::Float(float), Value::Integer(integer)) => {
+                integer_float_cmp(*integer, *float).reverse()
+            }
+            _ => {
+                let (left, right) = (self.as_f64(), other.as_f64());
+                left.partial_cmp(&right)
+                    .unwrap_or_else(|| left.is_nan().cmp(&right.is_nan()))
+            }
+        }
+    }
+
+    /// Rounds a float to the nearest multiple of `epsilon`, in lists as well,
+    /// leaves other values untouched.
+    ///
+    /// Rounding, as opposed to comparing within a tolerance, keeps equality
+    /// transitive: two floats are equal if they round to the same multiple.
+    pub(crate) fn round_to(&mut self, epsilon: f64) {
+        match self {
+            Value::Float(float) => *float = round(*float / epsilon) * epsilon,
+            Value::List(values) => values.iter_mut().for_each(|value| value.round_to(epsilon)),
+            _ => {}
+        }
+    }
+
+    /// Turns integral floats into integers, including `-0.0`, in lists as well.
+    ///
+    /// Only floats below 2^53 in magnitude are converted, since larger
+    /// floats are not guaranteed to denote a single integer.
+    pub(crate) fn normalize_number(&mut self) {
+        const MAX_EXACT: f64 = (1_u64 << 53) as f64;
+        match self {
+            Value::Float(float) if float.abs() < MAX_EXACT && *float == *float as i64 as f64 => {
+                *self = Value::Integer(*float as i64)
+            }
+            Value::List(values) => values.iter_mut().for_each(Value::normalize_number),
+            _ => {}
+        }
+    }
+
+    /// Replaces floats by their rendering with `precision` decimal places,
+    /// e.g., `0.30000000000000004` by `0.30`, also within lists.
+    pub(crate) fn format_float(&mut self, precision: usize) {
+        match self {
+            Value::Float(float) => *self = Value::String(format!("{:.*}", precision, float)),
+            Value::List(values) => values
+                .iter_mut()
+                .for_each(|value| value.format_float(precision)),
+            _ => {}
+        }
+    }
+
+    /// Replaces strings that parse as an integer or a finite float by that
+    /// number, also within lists.
+    pub(crate) fn coerce_numeric_string(&mut self) {
+        match self {
+            Value::String(string) => {
+                if let Ok(integer) = string.parse::<i64>() {
+                    *self = Value::Integer(integer);
+                } else if let Some(float) = string.parse::<f64>().ok().filter(|f| f.is_finite()) {
+                    *self = Value::Float(float);
+                }
+            }
+            Value::List(values) => values.iter_mut().for_each(Value::coerce_numeric_string),
+            _ => {}
+        }
+    }
+
+    /// Sorts the elements of a list, and of all nested lists, by `canonical_cmp`.
+    pub(crate) fn sort_lists(&mut self) {
+        if let Value::List(values) = self {
+            values.iter_mut().for_each(Value::sort_lists);
+            values.sort_by(Value::canonical_cmp);
+        }
+    }
+
+    /// Returns integers and floats as `f64`, `None` for everything else.
+    pub(crate) fn as_number(&self) -> Option<f64> {
+        match self {
+            Value::Integer(_) | Value::Float(_) => Some(self.as_f64()),
+            _ => None,
+        }
+    }
+
+    fn as_f64(&self) -> f64 {
+        match self {
+            Value::Integer(integer) => *integer as f64,
+            Value::Float(float) => *float,
+            _ => f64::NAN,
+        }
+    }
+}
+
+/// Compares an integer to a float exactly, even if the integer is not
+/// representable as a float. NaN is greater than every integer.
+fn integer_float_cmp(integer: i64, float: f64) -> Ordering {
+    match (integer as f64).partial_cmp(&float) {
+        // The float is integral and at most 2^63 in magnitude, so it fits.
+        Some(Ordering::Equal) => i128::from(integer).cmp(&(float as i128)),
+        Some(ordering) => ordering,
+        None => Ordering::Less,
+    }
+}
+
+#[cfg(feature = "std")]
+fn round(float: f64) -> f64 {
+    float.round()
+}
+
+/// Rounds half away from zero like `f64::round`, which needs `std`.
+#[cfg(not(feature = "std"))]
+fn round(float: f64) -> f64 {
+    // Larger floats, infinities and NaN are left as they are.
+    const MIN_INTEGRAL: f64 = (1_u64 << 52) as f64;
+    if float.is_nan() || float.abs() >= MIN_INTEGRAL {
+        return float;
+    }
+    let truncated = float as i64 as f64;
+    let rounded = if float - truncated >= 0.5 {
+        truncated + 1.0
+    } else if float - truncated <= -0.5 {
+        truncated - 1.0
+    } else {
+        truncated
+    };
+    rounded.copysign(float)
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Boolean(boolean) => write!(f, "{}", boolean),
+            Value::Integer(integer) => write!(f, "{}", integer),
+            Value::Float(float) => write!(f, "{}", float),
+            Value::String(string) => f.pad(string),
+            Value::List(values) => {
+                let values = values
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "[{}]", values)
+            }
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Boolean(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Integer(value.into())
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Integer(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(values: Vec<T>) -> Self {
+        Value::List(values.into_iter().map(Into::into).collect())
+    }
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "cli")]
+
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/");
+    let args = args.iter().map(|arg| match arg.strip_suffix(".gdl") {
+        Some(_) => format!("{}{}", fixtures, arg),
+        None => arg.to_string(),
+    });
+    Command::new(env!("CARGO_BIN_EXE_assert-graph-iso"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn test_isomorphic_files() {
+    let output = run(&["left.gdl", "right.gdl"]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "graphs are isomorphic\n");
+}
+
+#[test]
+fn test_non_isomorphic_files_print_diff() {
+    let output = run(&["left.gdl", "renumbered.gdl"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = stdout(&output);
+    assert!(stdout.starts_with("graphs are not isomorphic\n"));
+    assert!(stdout.contains("\n- (:Person { id: 1, name: Alice }) => out: "));
+    assert!(stdout.contains("\n+ (:Person { id: 10, name: Alice }) => out:  in: "));
+}
+
+#[test]
+fn test_flags_map_to_compare_options() {
+    let code = |args: &[&str]| run(args).status.code();
+
+    assert_eq!(
+        code(&["--ignore-key", "id", "left.gdl", "renumbered.gdl"]),
+        Some(1)
+    );
+    assert_eq!(
+        code(&[
+            "--ignore-key",
+            "id",
+            "--undirected",
+            "left.gdl",
+            "renumbered.gdl"
+        ]),
+        Some(0)
+    );
+    assert_eq!(
+        code(&["--topology-only", "left.gdl", "renumbered.gdl"]),
+        Some(0)
+    );
+}
+
+#[test]
+fn test_invalid_arguments() {
+    let output = run(&["left.gdl"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("usage: assert-graph-iso"));
+
+    let code = |args: &[&str]| run(args).status.code();
+    assert_eq!(code(&["--unknown", "left.gdl", "right.gdl"]), Some(2));
+    assert_eq!(code(&["left.gdl", "missing.gdl"]), Some(2));
+}